@@ -0,0 +1,266 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::adapters::{PayloadAdapter, render_template, truncate};
+use crate::api::{Priority, SendRequest};
+use crate::config::AlertmanagerConfig;
+use crate::error::{ProwlError, Result};
+
+const DEFAULT_EVENT_TEMPLATE: &str = "[{{status}}:{{count}}] {{labels.alertname}}";
+const DEFAULT_DESCRIPTION_TEMPLATE: &str = "{{annotations.summary}}";
+
+/// Alertmanager webhook body (version 4).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Notification {
+    #[serde(default)]
+    alerts: Vec<Alert>,
+    #[serde(default)]
+    common_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    common_annotations: BTreeMap<String, String>,
+    #[serde(default, rename = "externalURL")]
+    external_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Alert {
+    status: String,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+/// Sends one notification per status in each Alertmanager group: the firing alerts together
+/// at a priority taken from their `severity` labels, and the resolved ones at a lower priority.
+pub struct AlertmanagerAdapter {
+    config: AlertmanagerConfig,
+}
+
+impl AlertmanagerAdapter {
+    pub fn new(config: AlertmanagerConfig) -> Self {
+        AlertmanagerAdapter { config }
+    }
+
+    /// Priority for a firing alert with the given `severity` label.
+    pub fn severity_priority(&self, severity: &str) -> Priority {
+        if let Some(priority) = self.config.severity_priorities.get(severity) {
+            return *priority;
+        }
+        match severity.to_ascii_lowercase().as_str() {
+            "critical" | "page" | "emergency" => Priority::Emergency,
+            "error" | "high" | "major" => Priority::High,
+            "info" | "informational" | "low" => Priority::Moderate,
+            "none" => Priority::VeryLow,
+            _ => Priority::Normal,
+        }
+    }
+
+    /// Priority for resolved notices.
+    pub fn resolved_priority(&self) -> Priority {
+        self.config.resolved_priority.unwrap_or(Priority::Moderate)
+    }
+
+    fn build_request(
+        &self,
+        notification: &Notification,
+        alerts: &[&Alert],
+        firing: bool,
+        template: &SendRequest,
+    ) -> Result<SendRequest> {
+        let status = if firing { "FIRING" } else { "RESOLVED" };
+        let count = alerts.len().to_string();
+
+        let event = render_template(
+            self.config
+                .event_template
+                .as_deref()
+                .unwrap_or(DEFAULT_EVENT_TEMPLATE),
+            |name| {
+                lookup(
+                    name,
+                    status,
+                    &count,
+                    &notification.common_labels,
+                    &notification.common_annotations,
+                )
+            },
+        );
+
+        let description_template = self
+            .config
+            .description_template
+            .as_deref()
+            .unwrap_or(DEFAULT_DESCRIPTION_TEMPLATE);
+        let lines: Vec<String> = alerts
+            .iter()
+            .map(|alert| {
+                let line = render_template(description_template, |name| {
+                    lookup(name, status, &count, &alert.labels, &alert.annotations)
+                });
+                if line.trim().is_empty() {
+                    describe_labels(&alert.labels)
+                } else {
+                    line
+                }
+            })
+            .collect();
+        let mut description = lines.join("\n");
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        let priority = if firing {
+            alerts
+                .iter()
+                .map(|alert| {
+                    alert
+                        .labels
+                        .get("severity")
+                        .map(|severity| self.severity_priority(severity))
+                        .unwrap_or_default()
+                })
+                .max()
+                .unwrap_or_default()
+        } else {
+            self.resolved_priority()
+        };
+
+        let mut event = if event.trim().is_empty() {
+            template.event.clone()
+        } else {
+            event
+        };
+        truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+
+        let request = SendRequest {
+            event,
+            description,
+            priority: priority.as_i8(),
+            url: notification
+                .external_url
+                .clone()
+                .filter(|url| url.len() <= SendRequest::MAX_URL_LEN),
+            ..template.clone()
+        };
+        request.validate()?;
+        Ok(request)
+    }
+}
+
+impl PayloadAdapter for AlertmanagerAdapter {
+    fn adapt(&self, payload: &[u8], template: &SendRequest) -> Result<Vec<SendRequest>> {
+        let notification: Notification = serde_json::from_slice(payload)
+            .map_err(|e| ProwlError::InvalidInput(format!("Alertmanager payload: {e}")))?;
+
+        let (firing, resolved): (Vec<&Alert>, Vec<&Alert>) = notification
+            .alerts
+            .iter()
+            .partition(|alert| alert.status != "resolved");
+
+        let mut requests = Vec::new();
+        if !firing.is_empty() {
+            requests.push(self.build_request(&notification, &firing, true, template)?);
+        }
+        if !resolved.is_empty() {
+            requests.push(self.build_request(&notification, &resolved, false, template)?);
+        }
+        Ok(requests)
+    }
+}
+
+fn lookup<'a>(
+    name: &str,
+    status: &'a str,
+    count: &'a str,
+    labels: &'a BTreeMap<String, String>,
+    annotations: &'a BTreeMap<String, String>,
+) -> Option<&'a str> {
+    match name {
+        "status" => Some(status),
+        "count" => Some(count),
+        _ => {
+            if let Some(label) = name.strip_prefix("labels.") {
+                labels.get(label).map(String::as_str)
+            } else if let Some(annotation) = name.strip_prefix("annotations.") {
+                annotations.get(annotation).map(String::as_str)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Fallback description line for alerts without the annotations the template expects.
+fn describe_labels(labels: &BTreeMap<String, String>) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> SendRequest {
+        SendRequest {
+            application: "alertmanager".to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    const PAYLOAD: &str = r#"{
+        "version": "4",
+        "status": "firing",
+        "externalURL": "http://alertmanager:9093",
+        "commonLabels": {"alertname": "DiskFull"},
+        "commonAnnotations": {},
+        "alerts": [
+            {"status": "firing", "labels": {"alertname": "DiskFull", "severity": "warning", "instance": "a"},
+             "annotations": {"summary": "/ is 95% full on a"}},
+            {"status": "firing", "labels": {"alertname": "DiskFull", "severity": "critical", "instance": "b"},
+             "annotations": {}},
+            {"status": "resolved", "labels": {"alertname": "DiskFull", "severity": "critical", "instance": "c"},
+             "annotations": {"summary": "/ is fine on c"}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_groups_firing_and_resolved() {
+        let adapter = AlertmanagerAdapter::new(AlertmanagerConfig::default());
+        let requests = adapter.adapt(PAYLOAD.as_bytes(), &template()).unwrap();
+        assert_eq!(requests.len(), 2);
+
+        let firing = &requests[0];
+        assert_eq!(firing.event, "[FIRING:2] DiskFull");
+        assert_eq!(firing.priority, 2);
+        assert_eq!(
+            firing.description,
+            "/ is 95% full on a\nalertname=DiskFull, instance=b, severity=critical"
+        );
+        assert_eq!(firing.url.as_deref(), Some("http://alertmanager:9093"));
+
+        let resolved = &requests[1];
+        assert_eq!(resolved.event, "[RESOLVED:1] DiskFull");
+        assert_eq!(resolved.priority, -1);
+        assert_eq!(resolved.description, "/ is fine on c");
+    }
+
+    #[test]
+    fn test_configured_templates_and_priorities() {
+        let config = AlertmanagerConfig {
+            event_template: Some("{{labels.alertname}} ({{status}})".to_string()),
+            description_template: Some("{{labels.instance}}: {{annotations.summary}}".to_string()),
+            severity_priorities: BTreeMap::from([("critical".to_string(), Priority::High)]),
+            resolved_priority: Some(Priority::VeryLow),
+        };
+        let adapter = AlertmanagerAdapter::new(config);
+        let requests = adapter.adapt(PAYLOAD.as_bytes(), &template()).unwrap();
+
+        assert_eq!(requests[0].event, "DiskFull (FIRING)");
+        assert_eq!(requests[0].priority, 1);
+        assert_eq!(requests[0].description, "a: / is 95% full on a\nb: ");
+        assert_eq!(requests[1].priority, -2);
+    }
+}
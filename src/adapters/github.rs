@@ -0,0 +1,203 @@
+use serde::Deserialize;
+
+use crate::adapters::{PayloadAdapter, truncate};
+use crate::api::{Priority, SendRequest};
+use crate::error::{ProwlError, Result};
+
+/// The parts of a GitHub webhook body this adapter reads. Which event it is comes from the
+/// keys present, since the `X-GitHub-Event` header isn't available on stdin.
+#[derive(Debug, Deserialize)]
+struct Payload {
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    zen: Option<String>,
+    #[serde(default)]
+    repository: Option<Repository>,
+    #[serde(default)]
+    workflow_run: Option<WorkflowRun>,
+    #[serde(default)]
+    deployment_status: Option<DeploymentStatus>,
+    #[serde(default)]
+    deployment: Option<Deployment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowRun {
+    name: String,
+    #[serde(default)]
+    conclusion: Option<String>,
+    #[serde(default)]
+    head_branch: Option<String>,
+    #[serde(default)]
+    display_title: Option<String>,
+    #[serde(default)]
+    html_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeploymentStatus {
+    state: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    target_url: Option<String>,
+    #[serde(default)]
+    environment_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Deployment {
+    #[serde(default)]
+    environment: Option<String>,
+    #[serde(rename = "ref", default)]
+    git_ref: Option<String>,
+}
+
+/// Sends a notification when a GitHub Actions workflow run completes or a deployment
+/// reaches a final state. Failures go out at high priority, everything else at moderate.
+///
+/// Other activity for those events (runs being requested, deployments in progress) and
+/// the `ping` GitHub sends when a webhook is created produce no notifications.
+pub struct GitHubAdapter;
+
+impl PayloadAdapter for GitHubAdapter {
+    fn adapt(&self, payload: &[u8], template: &SendRequest) -> Result<Vec<SendRequest>> {
+        let payload: Payload = serde_json::from_slice(payload)
+            .map_err(|e| ProwlError::InvalidInput(format!("GitHub payload: {e}")))?;
+        let repo = payload
+            .repository
+            .as_ref()
+            .map_or("GitHub", |repo| repo.full_name.as_str());
+
+        let (event, mut description, priority, url) = if let Some(run) = payload.workflow_run {
+            if payload.action.as_deref() != Some("completed") {
+                return Ok(Vec::new());
+            }
+            let conclusion = run.conclusion.as_deref().unwrap_or("completed");
+            let branch = run
+                .head_branch
+                .map(|branch| format!(" on {branch}"))
+                .unwrap_or_default();
+            let mut description = format!("{} {conclusion}{branch}", run.name);
+            if let Some(title) = run.display_title {
+                description.push_str(&format!("\n{title}"));
+            }
+            (
+                format!("{repo}: {}", run.name),
+                description,
+                conclusion_priority(conclusion),
+                run.html_url,
+            )
+        } else if let Some(status) = payload.deployment_status {
+            if matches!(status.state.as_str(), "pending" | "queued" | "in_progress") {
+                return Ok(Vec::new());
+            }
+            let deployment = payload.deployment.as_ref();
+            let environment = deployment
+                .and_then(|deployment| deployment.environment.as_deref())
+                .unwrap_or("deployment");
+            let git_ref = deployment
+                .and_then(|deployment| deployment.git_ref.as_deref())
+                .map(|git_ref| format!(" of {git_ref}"))
+                .unwrap_or_default();
+            let mut description = format!("Deployment{git_ref} to {environment}: {}", status.state);
+            if let Some(detail) = status.description.filter(|detail| !detail.is_empty()) {
+                description.push_str(&format!("\n{detail}"));
+            }
+            (
+                format!("{repo}: {environment}"),
+                description,
+                conclusion_priority(&status.state),
+                status.target_url.or(status.environment_url),
+            )
+        } else if payload.zen.is_some() {
+            return Ok(Vec::new());
+        } else {
+            return Err(ProwlError::InvalidInput(
+                "GitHub payload: only workflow_run and deployment_status events are supported"
+                    .to_string(),
+            ));
+        };
+
+        let mut event = event;
+        truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        let request = SendRequest {
+            event,
+            description,
+            priority: priority.as_i8(),
+            url: url.filter(|url| !url.is_empty() && url.len() <= SendRequest::MAX_URL_LEN),
+            ..template.clone()
+        };
+        request.validate()?;
+        Ok(vec![request])
+    }
+}
+
+fn conclusion_priority(conclusion: &str) -> Priority {
+    match conclusion {
+        "failure" | "timed_out" | "startup_failure" | "error" => Priority::High,
+        _ => Priority::Moderate,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> SendRequest {
+        SendRequest {
+            application: "github".to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_workflow_run() {
+        let payload = r#"{
+            "action": "completed",
+            "repository": {"full_name": "octo/app"},
+            "workflow_run": {"name": "CI", "conclusion": "failure", "head_branch": "main",
+                             "display_title": "Fix parser", "html_url": "https://github.com/octo/app/actions/runs/1"}
+        }"#;
+        let requests = GitHubAdapter
+            .adapt(payload.as_bytes(), &template())
+            .unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].event, "octo/app: CI");
+        assert_eq!(requests[0].description, "CI failure on main\nFix parser");
+        assert_eq!(requests[0].priority, 1);
+        assert_eq!(
+            requests[0].url.as_deref(),
+            Some("https://github.com/octo/app/actions/runs/1")
+        );
+
+        let in_progress = payload.replace("\"completed\"", "\"in_progress\"");
+        assert!(
+            GitHubAdapter
+                .adapt(in_progress.as_bytes(), &template())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_unsupported_event() {
+        let ping = r#"{"zen": "Keep it logically awesome.", "hook_id": 1}"#;
+        assert!(
+            GitHubAdapter
+                .adapt(ping.as_bytes(), &template())
+                .unwrap()
+                .is_empty()
+        );
+        let push = r#"{"ref": "refs/heads/main", "repository": {"full_name": "octo/app"}}"#;
+        assert!(GitHubAdapter.adapt(push.as_bytes(), &template()).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::adapters::{AlertmanagerAdapter, PayloadAdapter, truncate};
+use crate::api::{Priority, SendRequest};
+use crate::config::AlertmanagerConfig;
+use crate::error::{ProwlError, Result};
+
+/// Grafana alerting webhook body. Unified alerting sends `status` and `alerts`; legacy
+/// dashboard alerts send `state`, `ruleName` and `ruleUrl` instead.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Notification {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    rule_name: Option<String>,
+    #[serde(default)]
+    rule_url: Option<String>,
+    #[serde(default, rename = "externalURL")]
+    external_url: Option<String>,
+    #[serde(default)]
+    common_labels: BTreeMap<String, String>,
+    #[serde(default)]
+    alerts: Vec<Alert>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Alert {
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+    #[serde(default, rename = "generatorURL")]
+    generator_url: Option<String>,
+}
+
+/// Sends one notification per Grafana webhook using Grafana's own title and message.
+///
+/// Priorities follow the `[alertmanager]` severity mapping, since Grafana alert rules carry
+/// the same kind of `severity` label.
+pub struct GrafanaAdapter {
+    severities: AlertmanagerAdapter,
+}
+
+impl GrafanaAdapter {
+    pub fn new(config: AlertmanagerConfig) -> Self {
+        GrafanaAdapter {
+            severities: AlertmanagerAdapter::new(config),
+        }
+    }
+}
+
+impl PayloadAdapter for GrafanaAdapter {
+    fn adapt(&self, payload: &[u8], template: &SendRequest) -> Result<Vec<SendRequest>> {
+        let notification: Notification = serde_json::from_slice(payload)
+            .map_err(|e| ProwlError::InvalidInput(format!("Grafana payload: {e}")))?;
+
+        let status = notification
+            .status
+            .as_deref()
+            .or(notification.state.as_deref())
+            .unwrap_or("firing");
+        let resolved = matches!(status, "resolved" | "ok");
+
+        let priority = if resolved {
+            self.severities.resolved_priority()
+        } else {
+            notification
+                .alerts
+                .iter()
+                .map(|alert| &alert.labels)
+                .chain(std::iter::once(&notification.common_labels))
+                .filter_map(|labels| labels.get("severity"))
+                .map(|severity| self.severities.severity_priority(severity))
+                .max()
+                .unwrap_or(Priority::High)
+        };
+
+        let mut event = notification
+            .title
+            .or(notification.rule_name)
+            .or_else(|| notification.common_labels.get("alertname").cloned())
+            .unwrap_or_else(|| template.event.clone());
+        truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+
+        let mut description = notification.message.unwrap_or_default();
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        let url = notification
+            .alerts
+            .iter()
+            .find_map(|alert| alert.generator_url.clone())
+            .or(notification.rule_url)
+            .or(notification.external_url)
+            .filter(|url| url.len() <= SendRequest::MAX_URL_LEN);
+
+        let request = SendRequest {
+            event,
+            description,
+            priority: priority.as_i8(),
+            url,
+            ..template.clone()
+        };
+        request.validate()?;
+        Ok(vec![request])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> SendRequest {
+        SendRequest {
+            application: "grafana".to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_unified_and_legacy_payloads() {
+        let adapter = GrafanaAdapter::new(AlertmanagerConfig::default());
+
+        let unified = r#"{
+            "status": "firing",
+            "title": "[FIRING:2] HighLatency",
+            "message": "p99 above 2s on api-1 and api-2",
+            "externalURL": "http://grafana:3000/",
+            "commonLabels": {"alertname": "HighLatency"},
+            "alerts": [
+                {"labels": {"severity": "warning"}, "generatorURL": "http://grafana:3000/alerting/1"},
+                {"labels": {"severity": "high"}, "generatorURL": "http://grafana:3000/alerting/2"}
+            ]
+        }"#;
+        let requests = adapter.adapt(unified.as_bytes(), &template()).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].event, "[FIRING:2] HighLatency");
+        assert_eq!(requests[0].description, "p99 above 2s on api-1 and api-2");
+        assert_eq!(requests[0].priority, 1);
+        assert_eq!(
+            requests[0].url.as_deref(),
+            Some("http://grafana:3000/alerting/1")
+        );
+
+        let legacy = format!(
+            r#"{{"state": "ok", "ruleName": "Disk usage", "ruleUrl": "http://grafana/{}"}}"#,
+            "d".repeat(SendRequest::MAX_URL_LEN)
+        );
+        let requests = adapter.adapt(legacy.as_bytes(), &template()).unwrap();
+        assert_eq!(requests[0].event, "Disk usage");
+        assert_eq!(requests[0].priority, -1);
+        assert_eq!(requests[0].url, None);
+
+        assert!(adapter.adapt(b"not json", &template()).is_err());
+    }
+}
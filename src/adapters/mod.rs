@@ -0,0 +1,100 @@
+pub mod alertmanager;
+pub mod github;
+pub mod grafana;
+pub mod pointer;
+
+use crate::api::SendRequest;
+use crate::config::Config;
+use crate::error::{ProwlError, Result};
+
+pub use alertmanager::AlertmanagerAdapter;
+pub use github::GitHubAdapter;
+pub use grafana::GrafanaAdapter;
+pub use pointer::JsonPointerAdapter;
+
+/// Adapters that are always available; `[adapters.<name>]` config entries add more.
+pub const BUILTIN: &[&str] = &["alertmanager", "github", "grafana"];
+
+/// Turns a webhook payload from another tool into notifications.
+pub trait PayloadAdapter: Send + Sync {
+    /// Builds zero or more requests from `payload`, taking the API key, application and any
+    /// unset fields from `template`. Returned requests have already been validated.
+    fn adapt(&self, payload: &[u8], template: &SendRequest) -> Result<Vec<SendRequest>>;
+}
+
+/// Looks up an adapter by name, built-in ones first.
+pub fn build(name: &str, config: &Config) -> Result<Box<dyn PayloadAdapter>> {
+    let alertmanager = || config.alertmanager.clone().unwrap_or_default();
+    match name {
+        "alertmanager" => Ok(Box::new(AlertmanagerAdapter::new(alertmanager()))),
+        "github" => Ok(Box::new(GitHubAdapter)),
+        "grafana" => Ok(Box::new(GrafanaAdapter::new(alertmanager()))),
+        _ => config
+            .adapters
+            .get(name)
+            .map(|adapter| -> Box<dyn PayloadAdapter> {
+                Box::new(JsonPointerAdapter::new(adapter.clone()))
+            })
+            .ok_or_else(|| {
+                ProwlError::Config(format!(
+                    "Unknown adapter: {name}. Built-in adapters are {}; others can be defined \
+                     under [adapters.<name>] in the config file",
+                    BUILTIN.join(", ")
+                ))
+            }),
+    }
+}
+
+/// Names of every adapter available with `config`.
+pub fn names(config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN.iter().map(|name| name.to_string()).collect();
+    names.extend(
+        config
+            .adapters
+            .keys()
+            .filter(|name| !BUILTIN.contains(&name.as_str()))
+            .cloned(),
+    );
+    names
+}
+
+/// Replaces each `{{ name }}` in `template` with `lookup(name)`, or nothing if it has no value.
+pub fn render_template<'a>(template: &str, lookup: impl Fn(&str) -> Option<&'a str>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        output.push_str(lookup(rest[start + 2..start + end].trim()).unwrap_or(""));
+        rest = &rest[start + end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Cuts `text` down to at most `max` bytes on a character boundary, marking the cut with "…".
+pub fn truncate(text: &mut String, max: usize) {
+    if text.len() <= max {
+        return;
+    }
+    let ellipsis = "…";
+    let mut end = max.saturating_sub(ellipsis.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str(ellipsis);
+}
+
+/// Renders a JSON value as notification text: strings as-is, everything else as JSON.
+pub fn value_to_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
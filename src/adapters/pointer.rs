@@ -0,0 +1,122 @@
+use serde_json::Value;
+
+use crate::adapters::{PayloadAdapter, truncate, value_to_text};
+use crate::api::{PriorityValue, SendRequest};
+use crate::config::JsonAdapterConfig;
+use crate::error::{ProwlError, Result};
+
+/// Builds one notification from an arbitrary JSON payload by reading each field at the
+/// JSON pointer (RFC 6901, e.g. `/alert/title`) configured under `[adapters.<name>]`.
+///
+/// Fields without a pointer, or whose pointer matches nothing, keep the template's value.
+pub struct JsonPointerAdapter {
+    config: JsonAdapterConfig,
+}
+
+impl JsonPointerAdapter {
+    pub fn new(config: JsonAdapterConfig) -> Self {
+        JsonPointerAdapter { config }
+    }
+
+    fn priority(&self, value: &Value) -> Result<i8> {
+        if let Some(priority) = self.config.priorities.get(&value_to_text(value)) {
+            return Ok(priority.as_i8());
+        }
+        serde_json::from_value::<PriorityValue>(value.clone())
+            .map_err(|_| ProwlError::InvalidPriority)?
+            .as_i8()
+    }
+}
+
+impl PayloadAdapter for JsonPointerAdapter {
+    fn adapt(&self, payload: &[u8], template: &SendRequest) -> Result<Vec<SendRequest>> {
+        let payload: Value = serde_json::from_slice(payload)
+            .map_err(|e| ProwlError::InvalidInput(format!("JSON payload: {e}")))?;
+        let field = |pointer: &Option<String>| {
+            pointer
+                .as_deref()
+                .and_then(|pointer| payload.pointer(pointer))
+                .filter(|value| !value.is_null())
+        };
+
+        let mut event = field(&self.config.event)
+            .map(value_to_text)
+            .filter(|event| !event.trim().is_empty())
+            .unwrap_or_else(|| template.event.clone());
+        truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+
+        let mut description = field(&self.config.description)
+            .map(value_to_text)
+            .unwrap_or_else(|| template.description.clone());
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        let priority = match field(&self.config.priority) {
+            Some(value) => self.priority(value)?,
+            None => template.priority,
+        };
+
+        let url = field(&self.config.url)
+            .map(value_to_text)
+            .filter(|url| url.len() <= SendRequest::MAX_URL_LEN)
+            .or_else(|| template.url.clone());
+
+        let request = SendRequest {
+            event,
+            description,
+            priority,
+            url,
+            ..template.clone()
+        };
+        request.validate()?;
+        Ok(vec![request])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Priority;
+    use std::collections::BTreeMap;
+
+    fn template() -> SendRequest {
+        SendRequest {
+            application: "uptime".to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_reads_configured_pointers() {
+        let adapter = JsonPointerAdapter::new(JsonAdapterConfig {
+            event: Some("/monitor/name".to_string()),
+            description: Some("/msg".to_string()),
+            priority: Some("/down".to_string()),
+            url: Some("/monitor/url".to_string()),
+            priorities: BTreeMap::from([("true".to_string(), Priority::Emergency)]),
+        });
+        let payload = r#"{"monitor": {"name": "api", "url": "https://example.com"},
+                          "msg": "api is down", "down": true}"#;
+        let requests = adapter.adapt(payload.as_bytes(), &template()).unwrap();
+        assert_eq!(requests[0].event, "api");
+        assert_eq!(requests[0].description, "api is down");
+        assert_eq!(requests[0].priority, 2);
+        assert_eq!(requests[0].url.as_deref(), Some("https://example.com"));
+
+        let up = payload.replace("true", "1");
+        let requests = adapter.adapt(up.as_bytes(), &template()).unwrap();
+        assert_eq!(requests[0].priority, 1);
+
+        let missing = r#"{"msg": "no monitor"}"#;
+        let requests = adapter.adapt(missing.as_bytes(), &template()).unwrap();
+        assert_eq!(requests[0].event, "Alert");
+        assert_eq!(requests[0].priority, 0);
+        assert_eq!(requests[0].url, None);
+
+        let long_url = payload.replace(
+            "https://example.com",
+            &"x".repeat(SendRequest::MAX_URL_LEN + 1),
+        );
+        let requests = adapter.adapt(long_url.as_bytes(), &template()).unwrap();
+        assert_eq!(requests[0].url, None);
+    }
+}
@@ -1,13 +1,24 @@
 use reqwest::Client;
+use std::sync::Arc;
 
 use crate::api::types::{ApiResponse, RegisterRequest, SendRequest, TokenRequest, VerifyRequest};
 use crate::api::xml::{parse_response, parse_token_response};
-use crate::error::Result;
+use crate::config::{Config, ResolvedConfig};
+use crate::error::{ProwlError, Result};
+use crate::failover::{Failover, RETRY_DELAY};
+use crate::history::History;
+use crate::notifiers::{BoxFuture, Notifier};
+use crate::rules::{Action, Rules};
 
 const BASE_URL: &str = "https://api.prowlapp.com/publicapi";
 
+#[derive(Clone)]
 pub struct ProwlClient {
     client: Client,
+    rules: Arc<Rules>,
+    history: Option<Arc<History>>,
+    backend: Option<Arc<dyn Notifier>>,
+    failover: Arc<Failover>,
 }
 
 impl ProwlClient {
@@ -15,12 +26,124 @@ impl ProwlClient {
         let client = Client::builder()
             .user_agent(format!("prowl-cli/{}", env!("CARGO_PKG_VERSION")))
             .build()?;
-        Ok(ProwlClient { client })
+        Ok(ProwlClient {
+            client,
+            rules: Arc::new(Rules::default()),
+            history: None,
+            backend: None,
+            failover: Arc::new(Failover::default()),
+        })
     }
 
+    /// A client set up the way the config file says: its rules, history and failover
+    /// chains, and the profile's backend.
+    pub fn from_config(config: &ResolvedConfig, file_config: &Config) -> Result<Self> {
+        Ok(Self::new()?
+            .with_rules(Rules::compile(&file_config.rules)?)
+            .with_history(History::from_config(file_config)?)
+            .with_backend(config.notifier()?)
+            .with_failover(Failover::compile(file_config)?))
+    }
+
+    /// Applies `rules` to every notification this client sends.
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.rules = Arc::new(rules);
+        self
+    }
+
+    /// Records every notification this client sends, or tries to, in `history`.
+    pub fn with_history(mut self, history: Option<History>) -> Self {
+        self.history = history.map(Arc::new);
+        self
+    }
+
+    /// Delivers through `backend` instead of the Prowl API. Rules and history still apply.
+    pub fn with_backend(mut self, backend: Option<Arc<dyn Notifier>>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Falls back along `failover`'s chains when a notification can't be delivered.
+    pub fn with_failover(mut self, failover: Failover) -> Self {
+        self.failover = Arc::new(failover);
+        self
+    }
+
+    /// Sends a notification after applying the client's rules, which may change it, drop it
+    /// or delay it until a quiet window ends; both are errors, and a delayed notification
+    /// comes back in [`ProwlError::Delayed`] for the caller to send later. If delivery
+    /// fails, it is retried and then sent through the failover chain for its priority, if
+    /// there is one. The outcome goes to the client's history, if it has one.
     pub async fn send(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let verdict = self.rules.evaluate(request, chrono::Local::now());
+        let (request, result) = match verdict.action {
+            Action::Send(request) => {
+                let result = self.deliver(&request).await;
+                (request, result)
+            }
+            Action::Drop => {
+                let result = Err(ProwlError::DroppedByRule {
+                    rule: verdict.rule.unwrap_or_default(),
+                });
+                (request.clone(), result)
+            }
+            Action::Delay { until, request } => {
+                let result = Err(ProwlError::Delayed {
+                    rule: verdict.rule.unwrap_or_default(),
+                    until,
+                    request: Box::new(request.clone()),
+                });
+                (request, result)
+            }
+        };
+
+        if let Some(history) = &self.history {
+            let via = match &result {
+                Ok(ApiResponse {
+                    channel: Some(channel),
+                    ..
+                }) => Some(channel.as_str()),
+                _ => self.backend.as_ref().map(|backend| backend.name()),
+            };
+            // Failing to write the history doesn't fail the send.
+            if let Err(_e) = history.record(&request, via, &result) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("Failed to record notification history: {_e}");
+            }
+        }
+        result
+    }
+
+    async fn deliver(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let Some(chain) = self.failover.chain(request.priority) else {
+            return self.add(request).await;
+        };
+
+        let mut result = self.add(request).await;
+        let mut delay = RETRY_DELAY;
+        for _ in 0..chain.retries {
+            match &result {
+                Err(e) if e.is_transient() => {}
+                _ => break,
+            }
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+            result = self.add(request).await;
+        }
+
+        match result {
+            Err(e) if e.is_delivery_failure() => chain.deliver(request, e).await,
+            result => result,
+        }
+    }
+
+    async fn add(&self, request: &SendRequest) -> Result<ApiResponse> {
         request.validate()?;
 
+        if let Some(backend) = &self.backend {
+            return backend.notify(request).await;
+        }
+
         let mut form = vec![
             ("apikey", request.apikey.clone()),
             ("application", request.application.clone()),
@@ -88,3 +211,83 @@ impl Default for ProwlClient {
         Self::new().expect("Failed to create HTTP client")
     }
 }
+
+impl Notifier for ProwlClient {
+    fn name(&self) -> &str {
+        self.backend
+            .as_ref()
+            .map_or("prowl", |backend| backend.name())
+    }
+
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+        Box::pin(self.send(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RuleAction, RuleConfig};
+    use crate::notifiers::stand_in::Recorder;
+    use chrono::{Duration, Local};
+
+    fn request(event: &str) -> SendRequest {
+        SendRequest {
+            apikey: "a".repeat(40),
+            application: "prowl-cli".to_string(),
+            event: event.to_string(),
+            description: String::new(),
+            priority: 0,
+            url: None,
+            providerkey: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delayed_notification_does_not_block() {
+        let now = Local::now();
+        let hours = format!(
+            "{}-{}",
+            (now - Duration::hours(1)).format("%H:%M"),
+            (now + Duration::hours(1)).format("%H:%M")
+        );
+        let rules = Rules::compile(&[RuleConfig {
+            name: "quiet".to_string(),
+            hours: Some(hours),
+            event: Some("^later$".to_string()),
+            action: RuleAction::Delay,
+            ..RuleConfig::default()
+        }])
+        .unwrap();
+        let recorder = Arc::new(Recorder::default());
+        let client = ProwlClient::new()
+            .unwrap()
+            .with_rules(rules)
+            .with_backend(Some(recorder.clone()));
+
+        let limit = std::time::Duration::from_secs(5);
+        let delayed = tokio::time::timeout(limit, client.send(&request("later")))
+            .await
+            .expect("the delayed send waited for its window");
+        match delayed {
+            Err(ProwlError::Delayed {
+                rule,
+                until,
+                request,
+            }) => {
+                assert_eq!(rule, "quiet");
+                assert!(until > now);
+                assert_eq!(request.event, "later");
+            }
+            other => panic!("expected a delay, got {other:?}"),
+        }
+
+        tokio::time::timeout(limit, client.send(&request("now")))
+            .await
+            .expect("the next send waited behind the delayed one")
+            .unwrap();
+        let received = recorder.received();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event, "now");
+    }
+}
@@ -3,4 +3,6 @@ pub mod types;
 pub mod xml;
 
 pub use client::ProwlClient;
-pub use types::{ApiResponse, RegisterRequest, SendRequest, TokenRequest, VerifyRequest};
+pub use types::{
+    ApiResponse, Priority, PriorityValue, RegisterRequest, SendRequest, TokenRequest, VerifyRequest,
+};
@@ -1,6 +1,6 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SendRequest {
     pub apikey: String,
     pub application: String,
@@ -53,6 +53,22 @@ impl SendRequest {
     }
 }
 
+#[cfg(test)]
+impl SendRequest {
+    /// A valid request for tests to build variants from with `..SendRequest::example()`.
+    pub(crate) fn example() -> Self {
+        Self {
+            apikey: "key".to_string(),
+            application: "prowl-cli".to_string(),
+            event: "Alert".to_string(),
+            description: String::new(),
+            priority: 0,
+            url: None,
+            providerkey: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct VerifyRequest {
     pub apikey: String,
@@ -81,6 +97,8 @@ pub struct ApiResponse {
     pub token_url: Option<String>,
     pub apikey: Option<String>,
     pub error_message: Option<String>,
+    /// The failover channel that delivered the notification after the primary failed.
+    pub channel: Option<String>,
 }
 
 impl ApiResponse {
@@ -94,6 +112,7 @@ impl ApiResponse {
             token_url: None,
             apikey: None,
             error_message: None,
+            channel: None,
         }
     }
 
@@ -107,4 +126,94 @@ impl ApiResponse {
         self.apikey = Some(apikey);
         self
     }
+
+    /// Marks the response as coming from failover `channel`, after `primary` failed.
+    pub fn via_failover(mut self, channel: &str, primary: &crate::error::ProwlError) -> Self {
+        self.channel = Some(channel.to_string());
+        self.error_message = Some(primary.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Priority {
+    /// Very low priority (-2)
+    VeryLow,
+    /// Moderate priority (-1)
+    Moderate,
+    #[default]
+    /// Normal priority (0)
+    Normal,
+    /// High priority (1)
+    High,
+    /// Emergency priority (2)
+    Emergency,
+}
+
+impl Priority {
+    pub fn as_i8(self) -> i8 {
+        match self {
+            Priority::VeryLow => -2,
+            Priority::Moderate => -1,
+            Priority::Normal => 0,
+            Priority::High => 1,
+            Priority::Emergency => 2,
+        }
+    }
+
+    /// Parses a `--priority` name such as "very-low", ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "very-low" => Some(Priority::VeryLow),
+            "moderate" => Some(Priority::Moderate),
+            "normal" => Some(Priority::Normal),
+            "high" => Some(Priority::High),
+            "emergency" => Some(Priority::Emergency),
+            _ => None,
+        }
+    }
+
+    /// The priority with API value `value`, if it is one.
+    pub fn from_i8(value: i8) -> Option<Self> {
+        match value {
+            -2 => Some(Priority::VeryLow),
+            -1 => Some(Priority::Moderate),
+            0 => Some(Priority::Normal),
+            1 => Some(Priority::High),
+            2 => Some(Priority::Emergency),
+            _ => None,
+        }
+    }
+
+    /// The name accepted by [`Priority::from_name`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Priority::VeryLow => "very-low",
+            Priority::Moderate => "moderate",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Emergency => "emergency",
+        }
+    }
+}
+
+/// A priority in a JSON or CSV payload: either the numeric API value or a `--priority` name.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PriorityValue {
+    Level(i8),
+    Name(String),
+}
+
+impl PriorityValue {
+    pub fn as_i8(&self) -> crate::error::Result<i8> {
+        match self {
+            PriorityValue::Level(level) => Ok(*level),
+            PriorityValue::Name(name) => Priority::from_name(name)
+                .map(Priority::as_i8)
+                .ok_or(crate::error::ProwlError::InvalidPriority),
+        }
+    }
 }
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+use crate::api::{self, ApiResponse, RegisterRequest, SendRequest, TokenRequest, VerifyRequest};
+use crate::error::Result;
+use crate::failover::Failover;
+use crate::history::History;
+use crate::notifiers::Notifier;
+use crate::rules::Rules;
+
+/// A synchronous [`api::ProwlClient`] for programs without an async runtime.
+///
+/// ```no_run
+/// use prowl::api::SendRequest;
+///
+/// let client = prowl::blocking::ProwlClient::new()?;
+/// client.send(&SendRequest {
+///     apikey: "API_KEY".to_string(),
+///     application: "backup".to_string(),
+///     event: "Backup finished".to_string(),
+///     description: "42 GiB in 18m".to_string(),
+///     priority: 0,
+///     url: None,
+///     providerkey: None,
+/// })?;
+/// # Ok::<(), prowl::error::ProwlError>(())
+/// ```
+///
+/// Each call runs the async client to completion on a runtime owned by this client, so it
+/// must not be called from inside an async runtime.
+pub struct ProwlClient {
+    inner: api::ProwlClient,
+    runtime: Runtime,
+}
+
+impl ProwlClient {
+    pub fn new() -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(ProwlClient {
+            inner: api::ProwlClient::new()?,
+            runtime,
+        })
+    }
+
+    /// Applies `rules` to every notification this client sends.
+    pub fn with_rules(mut self, rules: Rules) -> Self {
+        self.inner = self.inner.with_rules(rules);
+        self
+    }
+
+    /// Records every notification this client sends, or tries to, in `history`.
+    pub fn with_history(mut self, history: Option<History>) -> Self {
+        self.inner = self.inner.with_history(history);
+        self
+    }
+
+    /// Delivers through `backend` instead of the Prowl API. Rules and history still apply.
+    pub fn with_backend(mut self, backend: Option<Arc<dyn Notifier>>) -> Self {
+        self.inner = self.inner.with_backend(backend);
+        self
+    }
+
+    /// Falls back along `failover`'s chains when a notification can't be delivered.
+    pub fn with_failover(mut self, failover: Failover) -> Self {
+        self.inner = self.inner.with_failover(failover);
+        self
+    }
+
+    pub fn send(&self, request: &SendRequest) -> Result<ApiResponse> {
+        self.runtime.block_on(self.inner.send(request))
+    }
+
+    pub fn verify(&self, request: &VerifyRequest) -> Result<ApiResponse> {
+        self.runtime.block_on(self.inner.verify(request))
+    }
+
+    pub fn retrieve_token(&self, request: &TokenRequest) -> Result<ApiResponse> {
+        self.runtime.block_on(self.inner.retrieve_token(request))
+    }
+
+    pub fn retrieve_apikey(&self, request: &RegisterRequest) -> Result<ApiResponse> {
+        self.runtime.block_on(self.inner.retrieve_apikey(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ProwlError;
+    use crate::notifiers::stand_in::Recorder;
+
+    #[test]
+    fn test_send_validates_before_sending() {
+        let client = ProwlClient::new().unwrap();
+        let request = SendRequest {
+            apikey: "key".to_string(),
+            application: "prowl-cli".to_string(),
+            event: "x".repeat(SendRequest::MAX_EVENT_LEN + 1),
+            description: String::new(),
+            priority: 0,
+            url: None,
+            providerkey: None,
+        };
+        assert!(matches!(
+            client.send(&request),
+            Err(ProwlError::MessageTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_send_through_backend() {
+        let recorder = Arc::new(Recorder::default());
+        let client = ProwlClient::new()
+            .unwrap()
+            .with_backend(Some(recorder.clone()));
+        let request = SendRequest {
+            apikey: "a".repeat(40),
+            application: "backup".to_string(),
+            event: "Backup finished".to_string(),
+            description: String::new(),
+            priority: 0,
+            url: None,
+            providerkey: None,
+        };
+        assert_eq!(client.send(&request).unwrap().code, 200);
+        let received = recorder.received();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event, "Backup finished");
+    }
+}
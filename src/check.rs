@@ -0,0 +1,277 @@
+use regex::Regex;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::adapters::truncate;
+use crate::config::Config;
+use crate::error::{ProwlError, Result};
+use crate::store::LockedStore;
+
+/// Timeout for checks that don't configure one.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest part of the response body kept for notifications.
+const SNIPPET_LEN: usize = 200;
+
+/// An HTTP endpoint to check, from `[checks.<name>]` or the command line.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub url: Url,
+    /// Status the response must have; any 2xx if unset.
+    pub expect_status: Option<u16>,
+    pub expect_body: Option<Regex>,
+    pub timeout: Duration,
+}
+
+impl Check {
+    /// A check named after its URL.
+    pub fn from_url(
+        url: &str,
+        expect_status: Option<u16>,
+        expect_body: Option<Regex>,
+        timeout: Duration,
+    ) -> Result<Check> {
+        Ok(Check {
+            name: url.to_string(),
+            url: parse_url(url)?,
+            expect_status,
+            expect_body,
+            timeout,
+        })
+    }
+
+    /// Every check in the config file, by name.
+    pub fn load_all(config: &Config) -> Result<Vec<Check>> {
+        config
+            .checks
+            .iter()
+            .map(|(name, check)| {
+                let invalid = |field: &str, e: &dyn std::fmt::Display| {
+                    ProwlError::Config(format!("Check \"{name}\": invalid {field}: {e}"))
+                };
+                Ok(Check {
+                    name: name.clone(),
+                    url: parse_url(&check.url).map_err(|e| invalid("url", &e))?,
+                    expect_status: check.expect_status,
+                    expect_body: check
+                        .expect_body
+                        .as_deref()
+                        .map(Regex::new)
+                        .transpose()
+                        .map_err(|e| invalid("expect_body", &e))?,
+                    timeout: check
+                        .timeout
+                        .as_deref()
+                        .map(humantime::parse_duration)
+                        .transpose()
+                        .map_err(|e| invalid("timeout", &e))?
+                        .unwrap_or(DEFAULT_TIMEOUT),
+                })
+            })
+            .collect()
+    }
+
+    /// Requests the URL and judges the response.
+    pub async fn probe(&self, client: &Client) -> Probe {
+        let started = Instant::now();
+        let response = client
+            .get(self.url.clone())
+            .timeout(self.timeout)
+            .send()
+            .await;
+        let result = match response {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                response.text().await.map(|body| (status, body))
+            }
+            Err(e) => Err(e),
+        };
+        let latency = started.elapsed();
+
+        let (status, body) = match result {
+            Ok(response) => response,
+            Err(e) => {
+                let failure = if e.is_timeout() {
+                    format!(
+                        "No response within {}",
+                        humantime::format_duration(self.timeout)
+                    )
+                } else {
+                    format!("Request failed: {}", root_cause(&e))
+                };
+                return Probe {
+                    status: None,
+                    latency,
+                    snippet: String::new(),
+                    failure: Some(failure),
+                };
+            }
+        };
+
+        let failure = match self.expect_status {
+            Some(expected) if status != expected => {
+                Some(format!("HTTP {status}, expected {expected}"))
+            }
+            None if !(200..300).contains(&status) => Some(format!("HTTP {status}")),
+            _ => self
+                .expect_body
+                .as_ref()
+                .filter(|pattern| !pattern.is_match(&body))
+                .map(|pattern| format!("Body doesn't match /{pattern}/")),
+        };
+
+        Probe {
+            status: Some(status),
+            latency,
+            snippet: snippet(&body),
+            failure,
+        }
+    }
+}
+
+/// The outcome of one request.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    /// `None` if no response arrived.
+    pub status: Option<u16>,
+    pub latency: Duration,
+    /// The start of the body, with runs of whitespace collapsed.
+    pub snippet: String,
+    /// Why the check failed, if it did.
+    pub failure: Option<String>,
+}
+
+impl Probe {
+    pub fn is_up(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Which checks have been reported down.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckState {
+    #[serde(default)]
+    pub checks: BTreeMap<String, CheckStatus>,
+}
+
+/// Times are Unix seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckStatus {
+    /// When the check was found down, once the "down" notification has gone out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub down_since: Option<i64>,
+}
+
+/// A change worth notifying about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Down { since: i64 },
+    Up { down_since: i64 },
+}
+
+/// The check state file, locked while in use.
+pub type CheckFile = LockedStore<CheckState>;
+
+impl CheckState {
+    pub fn open() -> Result<CheckFile> {
+        LockedStore::open("checks.toml", "check state")
+    }
+
+    /// The transition a probe at `now` makes; checks never seen before count as up.
+    pub fn transition(&self, name: &str, up: bool, now: i64) -> Option<Transition> {
+        let down_since = self.checks.get(name).and_then(|check| check.down_since);
+        match (up, down_since) {
+            (false, None) => Some(Transition::Down { since: now }),
+            (true, Some(down_since)) => Some(Transition::Up { down_since }),
+            _ => None,
+        }
+    }
+
+    /// Records that the notification for `transition` went out.
+    pub fn record(&mut self, name: &str, transition: Transition) {
+        let check = self.checks.entry(name.to_string()).or_default();
+        check.down_since = match transition {
+            Transition::Down { since } => Some(since),
+            Transition::Up { .. } => None,
+        };
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+        .ok_or_else(|| ProwlError::InvalidInput(format!("Not an http(s) URL: {url}")))
+}
+
+/// The innermost error, which names the actual problem (refused, DNS, TLS).
+fn root_cause(error: &reqwest::Error) -> String {
+    let mut cause: &dyn std::error::Error = error;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+fn snippet(body: &str) -> String {
+    let mut snippet = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    truncate(&mut snippet, SNIPPET_LEN);
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifiers::stand_in;
+
+    #[tokio::test]
+    async fn test_probe_and_transitions() {
+        let client = Client::new();
+        let check = |url: &str, body: Option<&str>| {
+            Check::from_url(
+                &format!("{url}/health"),
+                Some(200),
+                body.map(|body| Regex::new(body).unwrap()),
+                DEFAULT_TIMEOUT,
+            )
+            .unwrap()
+        };
+
+        let (url, server) = stand_in::serve(200, "{\"status\": \"ok\"}");
+        let probe = check(&url, Some("\"ok\"")).probe(&client).await;
+        assert!(server.join().unwrap().starts_with("GET /health "));
+        assert!(probe.is_up(), "{probe:?}");
+        assert_eq!(probe.status, Some(200));
+        assert_eq!(probe.snippet, "{\"status\": \"ok\"}");
+
+        let (url, _) = stand_in::serve(200, "{\"status\": \"degraded\"}");
+        let probe = check(&url, Some("\"ok\"")).probe(&client).await;
+        assert_eq!(
+            probe.failure.as_deref(),
+            Some("Body doesn't match /\"ok\"/")
+        );
+
+        let (url, _) = stand_in::serve(503, "busy");
+        let probe = check(&url, None).probe(&client).await;
+        assert_eq!(probe.failure.as_deref(), Some("HTTP 503, expected 200"));
+
+        let mut state = CheckState::default();
+        assert_eq!(state.transition("api", true, 100), None);
+        let down = state.transition("api", false, 100).unwrap();
+        assert_eq!(down, Transition::Down { since: 100 });
+        // Until the notification is recorded as sent, the next probe tries again.
+        assert_eq!(
+            state.transition("api", false, 160),
+            Some(Transition::Down { since: 160 })
+        );
+        state.record("api", down);
+        assert_eq!(state.transition("api", false, 160), None);
+        let up = state.transition("api", true, 220).unwrap();
+        assert_eq!(up, Transition::Up { down_since: 100 });
+        state.record("api", up);
+        assert_eq!(state.transition("api", true, 280), None);
+    }
+}
@@ -1,5 +1,13 @@
 use clap::{Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::api::Priority;
+use crate::config::{MissedPolicy, ResolvedConfig};
+use crate::history::SendStatus;
+use crate::syslog::SyslogSeverity;
 
 #[derive(Parser)]
 #[command(
@@ -30,6 +38,10 @@ pub struct Cli {
     /// Application name for notifications
     #[arg(long, short = 'a', global = true, env = "PROWL_APPLICATION")]
     pub application: Option<String>,
+
+    /// Named profile from the config file to take credentials from
+    #[arg(long, short = 'P', global = true, env = "PROWL_PROFILE")]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -40,14 +52,58 @@ pub enum Command {
 
     /// Verify API key validity
     #[command(visible_alias = "v")]
-    Verify,
+    Verify(VerifyArgs),
 
     /// Get a registration token (for app developers)
-    Token,
+    Token(TokenArgs),
 
     /// Get API key from an approved registration token
     Register(RegisterArgs),
 
+    /// Manage user API keys collected as a provider
+    #[command(subcommand)]
+    Users(UsersCommand),
+
+    /// Run an HTTP webhook receiver that forwards notifications to Prowl
+    Serve(ServeArgs),
+
+    /// Hold the API key and send notifications for local users over a Unix socket
+    #[cfg(unix)]
+    Daemon(DaemonArgs),
+
+    /// Receive syslog messages and forward the matching ones as notifications
+    Syslog(SyslogArgs),
+
+    /// Accept mail over SMTP and forward it as notifications
+    Smtp(SmtpArgs),
+
+    /// Convert a webhook payload from another tool (read from stdin) into notifications
+    Adapt(AdaptArgs),
+
+    /// Manage notifications scheduled with `send --at` or `send --in`
+    #[command(subcommand)]
+    Schedule(ScheduleCommand),
+
+    /// Watch for jobs that stop pinging (dead man's switch)
+    #[command(subcommand)]
+    Heartbeat(HeartbeatCommand),
+
+    /// Check HTTP endpoints and notify when they go down or come back up
+    Check(CheckArgs),
+
+    /// Wait for a process, file, port or command, then send a notification
+    Wait(WaitArgs),
+
+    /// Show the notifications this machine has sent
+    History(HistoryArgs),
+
+    /// Read a mail message on stdin and send it as a notification, like sendmail
+    Sendmail(SendmailArgs),
+
+    /// Check the [[rules]] in the config file
+    #[command(subcommand)]
+    Rules(RulesCommand),
+
     /// Manage configuration
     #[command(subcommand)]
     Config(ConfigCommand),
@@ -61,7 +117,7 @@ pub enum Command {
 }
 
 #[derive(clap::Args)]
-pub struct SendArgs {
+pub struct NotificationArgs {
     /// Message to send (use "-" to read from stdin)
     #[arg(default_value = "")]
     pub message: String,
@@ -70,28 +126,575 @@ pub struct SendArgs {
     #[arg(long, short = 'e', default_value = "Alert")]
     pub event: String,
 
-    /// Priority level
-    #[arg(long, short = 'p', default_value = "normal")]
-    pub priority: Priority,
+    /// Priority level [default: the target URL's priority, or normal]
+    #[arg(long, short = 'p')]
+    pub priority: Option<Priority>,
 
     /// URL to attach to notification
     #[arg(long, short = 'u')]
     pub url: Option<String>,
+}
+
+impl NotificationArgs {
+    /// The `--priority` flag, falling back to the target URL's priority.
+    pub fn priority(&self, config: &ResolvedConfig) -> Priority {
+        self.priority.or(config.priority).unwrap_or_default()
+    }
+}
+
+#[derive(clap::Args)]
+pub struct SendArgs {
+    #[command(flatten)]
+    pub notification: NotificationArgs,
 
-    /// Additional API keys to send to (comma-separated or repeated)
-    #[arg(long, short = 't', value_delimiter = ',')]
+    /// Additional API keys or prowl:// URLs to send to (comma-separated keys, or repeated)
+    #[arg(long, short = 't')]
     pub to: Vec<String>,
 
     /// Show what would be sent without actually sending
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Send one notification per record from a JSON Lines or CSV file ("-" for stdin)
+    #[arg(long, value_name = "PATH", conflicts_with = "message")]
+    pub batch: Option<PathBuf>,
+
+    /// Read a JSON payload from stdin and convert it with --adapter
+    #[arg(long, requires = "adapter", conflicts_with_all = ["message", "batch"])]
+    pub from_json: bool,
+
+    /// Payload adapter for --from-json (see `prowl adapt --help`)
+    #[arg(long, value_name = "NAME", requires = "from_json")]
+    pub adapter: Option<String>,
+
+    /// Schedule the notification for this local time instead of sending it now
+    /// (e.g. "2026-10-19T09:00"); `prowl schedule run` sends it
+    #[arg(
+        long,
+        value_name = "TIME",
+        value_parser = crate::rules::parse_local_time,
+        conflicts_with_all = ["batch", "from_json", "in_"]
+    )]
+    pub at: Option<chrono::DateTime<chrono::Local>>,
+
+    /// Schedule the notification to be sent after this long (e.g. "2h", "1d 30m")
+    #[arg(
+        long = "in",
+        id = "in_",
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        conflicts_with_all = ["batch", "from_json"]
+    )]
+    pub in_: Option<Duration>,
+
+    /// Suppress repeats of this notification for this long after it is sent (e.g. "10m")
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = humantime::parse_duration,
+        conflicts_with_all = ["batch", "from_json", "at", "in_"]
+    )]
+    pub throttle: Option<Duration>,
+
+    /// Key identifying repeats for --throttle [default: a hash of the event and message]
+    #[arg(long, value_name = "KEY", requires = "throttle")]
+    pub dedup_key: Option<String>,
+
+    /// Send a "suppressed N repeats" notification once the --throttle window has closed, with
+    /// the next throttled send or `prowl schedule run`
+    #[arg(long, requires = "throttle")]
+    pub summary: bool,
+
+    /// Hand the notification to `prowl daemon` instead of sending it with a local API key
+    #[cfg(unix)]
+    #[arg(long, conflicts_with_all = ["batch", "from_json", "dry_run", "throttle", "at", "in_"])]
+    pub via_socket: bool,
+
+    /// Socket of the daemon for --via-socket [default: [daemon] socket in the config file,
+    /// then /run/prowl/prowl.sock]
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", env = "PROWL_SOCKET")]
+    pub socket: Option<PathBuf>,
+
+    /// Format of the --batch file [default: from the file extension, JSON Lines otherwise]
+    #[arg(long, value_name = "FORMAT", requires = "batch")]
+    pub batch_format: Option<BatchFormat>,
+
+    /// Minimum delay between batch sends, to stay within the API rate limit
+    #[arg(
+        long,
+        default_value = "1s",
+        value_parser = humantime::parse_duration,
+        requires = "batch"
+    )]
+    pub pace: Duration,
+}
+
+#[derive(clap::Args)]
+pub struct VerifyArgs {
+    /// Verify every API key in the config file (top level and all profiles)
+    #[arg(long)]
+    pub all: bool,
+
+    /// Verify the API keys listed in a file, one per line as "<key>" or "<label> <key>"
+    #[arg(long, value_name = "PATH")]
+    pub keys_file: Option<PathBuf>,
+
+    /// Maximum number of keys to verify at once
+    #[arg(long, short = 'j', default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub concurrency: u16,
+}
+
+#[derive(clap::Args)]
+pub struct TokenArgs {
+    #[command(flatten)]
+    pub qr: QrArgs,
+}
+
+/// `--qr`, for commands that show an approval URL.
+#[derive(clap::Args)]
+pub struct QrArgs {
+    /// Show the approval URL as a QR code [default: auto, when stdout is a wide enough terminal]
+    #[arg(
+        long = "qr",
+        id = "qr",
+        value_name = "WHEN",
+        default_value = "auto",
+        default_missing_value = "always",
+        num_args = 0..=1,
+        require_equals = true,
+        hide_default_value = true
+    )]
+    pub mode: QrMode,
 }
 
 #[derive(clap::Args)]
 pub struct RegisterArgs {
     /// Registration token from `prowl token`
-    #[arg(long, short = 't', required = true)]
-    pub token: String,
+    #[arg(long, short = 't', required_unless_present = "interactive")]
+    pub token: Option<String>,
+
+    /// Retrieve a token, wait for the user to approve it, then fetch the API key
+    #[arg(long, short = 'i', visible_alias = "wait", conflicts_with = "token")]
+    pub interactive: bool,
+
+    /// Open the approval URL in the default browser
+    #[arg(long, requires = "interactive")]
+    pub open: bool,
+
+    /// How long to wait for approval (e.g. "90s", "5m")
+    #[arg(
+        long,
+        default_value = "5m",
+        value_parser = humantime::parse_duration,
+        requires = "interactive"
+    )]
+    pub timeout: Duration,
+
+    #[command(flatten)]
+    pub qr: QrArgs,
+
+    /// Save the retrieved API key to the config file
+    #[arg(long)]
+    pub save: bool,
+
+    /// Save the retrieved API key to this named profile instead of the top level
+    #[arg(long, value_name = "NAME")]
+    pub save_profile: Option<String>,
+
+    /// Record the retrieved API key in the provider key registry under this label
+    #[arg(long, short = 'l')]
+    pub label: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum UsersCommand {
+    /// List registered users
+    List,
+
+    /// Add a user API key to the registry
+    Add {
+        /// Label identifying the user
+        label: String,
+        /// The user's API key
+        api_key: String,
+    },
+
+    /// Remove a user from the registry
+    Remove {
+        /// Label identifying the user
+        label: String,
+    },
+
+    /// Verify registered API keys using the provider key
+    Verify {
+        /// Only verify these users (comma-separated or repeated)
+        #[arg(long, short = 'u', value_delimiter = ',')]
+        users: Vec<String>,
+    },
+
+    /// Verify registered API keys and remove the ones the API rejects
+    Prune {
+        /// Show what would be removed without changing the registry
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Send a notification to registered users
+    Send(UsersSendArgs),
+}
+
+#[derive(clap::Args)]
+pub struct UsersSendArgs {
+    #[command(flatten)]
+    pub notification: NotificationArgs,
+
+    /// Only send to these users (comma-separated or repeated); defaults to everyone
+    #[arg(long = "user", short = 'U', value_delimiter = ',')]
+    pub users: Vec<String>,
+
+    /// Show what would be sent without actually sending
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, short = 'l', default_value = "127.0.0.1:8080")]
+    pub listen: SocketAddr,
+
+    /// Shared secret callers must present as "Authorization: Bearer <token>"
+    #[arg(long, env = "PROWL_SERVE_TOKEN", hide_env_values = true)]
+    pub auth_token: String,
+
+    /// Maximum number of notifications waiting to be delivered
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u16).range(1..))]
+    pub queue_size: u16,
+
+    /// Minimum delay between deliveries, to stay within the API rate limit
+    #[arg(long, default_value = "1s", value_parser = humantime::parse_duration)]
+    pub pace: Duration,
+}
+
+#[derive(clap::Args)]
+pub struct DaemonArgs {
+    /// Socket to listen on [default: [daemon] socket in the config file, then
+    /// /run/prowl/prowl.sock]
+    #[arg(long, value_name = "PATH", env = "PROWL_SOCKET")]
+    pub socket: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+pub struct SyslogArgs {
+    /// Where to listen, as udp://HOST:PORT or tcp://HOST:PORT
+    #[arg(long, short = 'l', default_value = "udp://0.0.0.0:5514")]
+    pub listen: ListenAddr,
+
+    /// Only forward messages from these facilities, by name or number (comma-separated or
+    /// repeated) [default: all]
+    #[arg(long, value_delimiter = ',', value_parser = crate::syslog::parse_facility)]
+    pub facility: Vec<u8>,
+
+    /// Only forward messages at least this severe
+    #[arg(long, short = 's', default_value = "warning")]
+    pub severity: SyslogSeverity,
+
+    /// Only forward messages from these programs (comma-separated or repeated) [default: all]
+    #[arg(long, value_delimiter = ',')]
+    pub program: Vec<String>,
+
+    /// Only forward messages whose text matches this regular expression
+    #[arg(long = "match", short = 'm', value_name = "REGEX")]
+    pub pattern: Option<regex::Regex>,
+
+    /// Collect messages from the same host and program for this long and send them as one
+    /// notification ("0s" sends each message on its own)
+    #[arg(long, default_value = "10s", value_parser = humantime::parse_duration)]
+    pub coalesce: Duration,
+
+    /// Maximum number of notifications waiting to be delivered
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u16).range(1..))]
+    pub queue_size: u16,
+
+    /// Minimum delay between deliveries, to stay within the API rate limit
+    #[arg(long, default_value = "1s", value_parser = humantime::parse_duration)]
+    pub pace: Duration,
+
+    /// Show the notifications that would be sent instead of sending them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args)]
+pub struct SmtpArgs {
+    /// Address and port to accept mail on
+    #[arg(long, short = 'l', default_value = "127.0.0.1:2525")]
+    pub listen: SocketAddr,
+
+    /// Maximum number of notifications waiting to be delivered
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u16).range(1..))]
+    pub queue_size: u16,
+
+    /// Minimum delay between deliveries, to stay within the API rate limit
+    #[arg(long, default_value = "1s", value_parser = humantime::parse_duration)]
+    pub pace: Duration,
+
+    /// Show the notifications that would be sent instead of sending them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Transport and address for `prowl syslog --listen`.
+#[derive(Debug, Clone, Copy)]
+pub enum ListenAddr {
+    Udp(SocketAddr),
+    Tcp(SocketAddr),
+}
+
+impl std::str::FromStr for ListenAddr {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let (scheme, addr) = value.split_once("://").unwrap_or(("udp", value));
+        let addr: SocketAddr = addr.parse().map_err(|e| format!("{e}"))?;
+        match scheme {
+            "udp" => Ok(ListenAddr::Udp(addr)),
+            "tcp" => Ok(ListenAddr::Tcp(addr)),
+            _ => Err(format!(
+                "unsupported transport \"{scheme}\" (expected udp or tcp)"
+            )),
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct AdaptArgs {
+    /// Payload format to convert from: alertmanager, github, grafana, or an adapter
+    /// defined under [adapters.<name>] in the config file
+    pub adapter: String,
+
+    /// Read the payload from a file instead of stdin
+    #[arg(long, short = 'i', value_name = "PATH")]
+    pub input: Option<PathBuf>,
+
+    /// Show what would be sent without actually sending
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleCommand {
+    /// List scheduled notifications
+    List,
+
+    /// Cancel scheduled notifications
+    Cancel {
+        /// Ids from `prowl schedule list`
+        #[arg(required = true)]
+        ids: Vec<u64>,
+    },
+
+    /// Send the notifications that are due
+    Run(ScheduleRunArgs),
+}
+
+#[derive(clap::Args)]
+pub struct ScheduleRunArgs {
+    /// Keep running and check for due notifications every --interval, instead of
+    /// sending what is due once and exiting
+    #[arg(long, short = 'w')]
+    pub watch: bool,
+
+    /// How often to check for due notifications with --watch
+    #[arg(
+        long,
+        default_value = "30s",
+        value_parser = humantime::parse_duration,
+        requires = "watch"
+    )]
+    pub interval: Duration,
+
+    /// How late a notification may still go out as normal [default: [schedule] grace in the
+    /// config file, then 1h]
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub grace: Option<Duration>,
+
+    /// What to do with notifications later than the grace period [default: [schedule]
+    /// missed in the config file, then send]
+    #[arg(long, value_name = "POLICY")]
+    pub missed: Option<MissedPolicy>,
+}
+
+#[derive(Subcommand)]
+pub enum HeartbeatCommand {
+    /// Record that a job ran
+    Ping {
+        /// Heartbeat name, as configured under [heartbeats.<name>]
+        name: String,
+    },
+
+    /// Show when each heartbeat was last pinged and when it is next due
+    List,
+
+    /// Notify about heartbeats that missed their interval, and ones that recovered
+    Check(HeartbeatCheckArgs),
+}
+
+#[derive(clap::Args)]
+pub struct HeartbeatCheckArgs {
+    /// Keep running and check every --interval, instead of checking once and exiting
+    #[arg(long, short = 'w')]
+    pub watch: bool,
+
+    /// How often to check with --watch
+    #[arg(
+        long,
+        default_value = "1m",
+        value_parser = humantime::parse_duration,
+        requires = "watch"
+    )]
+    pub interval: Duration,
+
+    /// Print the notifications instead of sending them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args)]
+pub struct CheckArgs {
+    /// URLs to check, or names of [checks.<name>] entries [default: every configured check]
+    #[arg(value_name = "URL|NAME")]
+    pub targets: Vec<String>,
+
+    /// Keep running and check this often, instead of checking once and exiting
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub every: Option<Duration>,
+
+    /// Status code the URLs given here must respond with [default: any 2xx]
+    #[arg(long, value_name = "CODE")]
+    pub expect_status: Option<u16>,
+
+    /// Regular expression the response body of the URLs given here must match
+    #[arg(long, value_name = "REGEX")]
+    pub expect_body: Option<regex::Regex>,
+
+    /// How long to wait for each URL given here to respond
+    #[arg(long, default_value = "10s", value_parser = humantime::parse_duration)]
+    pub timeout: Duration,
+
+    /// Print the notifications instead of sending them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args)]
+#[command(group(clap::ArgGroup::new("condition").required(true)))]
+pub struct WaitArgs {
+    /// Wait for the process with this ID to exit
+    #[arg(long, group = "condition")]
+    pub pid: Option<u32>,
+
+    /// Wait until this file or directory exists
+    #[arg(long, value_name = "PATH", group = "condition")]
+    pub file_exists: Option<PathBuf>,
+
+    /// Wait until HOST:PORT accepts TCP connections
+    #[arg(long, value_name = "HOST:PORT", group = "condition")]
+    pub port_open: Option<String>,
+
+    /// Wait until this shell command exits successfully, running it every --interval
+    #[arg(long, value_name = "COMMAND", group = "condition")]
+    pub until: Option<String>,
+
+    /// Give up after this long, and send a high-priority notification saying so
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub timeout: Option<Duration>,
+
+    /// How often to check the condition
+    #[arg(long, default_value = "2s", value_parser = humantime::parse_duration)]
+    pub interval: Duration,
+
+    /// Event title [default: what happened, e.g. "Process 1234 (rsync) exited"]
+    #[arg(long, short = 'e')]
+    pub event: Option<String>,
+
+    /// Priority once the condition holds [default: the target URL's priority, or normal]
+    #[arg(long, short = 'p')]
+    pub priority: Option<Priority>,
+
+    /// Print the notification instead of sending it
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Takes sendmail's own options, so that cron and other tools that send mail can use prowl
+/// in its place (also by symlinking `sendmail` to the prowl binary).
+#[derive(clap::Args)]
+#[command(after_help = "Supported sendmail options:\n  \
+    -t           Also send to the To, Cc and Bcc addresses in the message\n  \
+    -i, -oi      Don't treat a line with a single \".\" as the end of the message\n  \
+    -F <name>    Application name for the notification\n  \
+    -bv          Show the notification instead of sending it\n  \
+    -v           Report the result\n\n\
+    -f, -r, -B, -N, -o<option> and other delivery options are accepted and ignored.")]
+pub struct SendmailArgs {
+    /// Sendmail options, then recipient addresses
+    #[arg(
+        allow_hyphen_values = true,
+        trailing_var_arg = true,
+        value_name = "ARGS"
+    )]
+    pub args: Vec<String>,
+}
+
+#[derive(clap::Args)]
+pub struct HistoryArgs {
+    /// Only sends after this local time (e.g. "2026-10-19T09:00"), or within this long
+    /// before now (e.g. "2h")
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<chrono::DateTime<chrono::Local>>,
+
+    /// Only sends with this outcome
+    #[arg(long)]
+    pub status: Option<SendStatus>,
+
+    /// Only sends whose event matches this regular expression
+    #[arg(long, short = 'e', value_name = "REGEX")]
+    pub event: Option<regex::Regex>,
+
+    /// Show at most this many of the most recent matching sends (0 for all)
+    #[arg(long, short = 'n', default_value_t = 50)]
+    pub limit: usize,
+}
+
+fn parse_since(value: &str) -> Result<chrono::DateTime<chrono::Local>, String> {
+    match humantime::parse_duration(value) {
+        Ok(ago) => chrono::Duration::from_std(ago)
+            .ok()
+            .and_then(|ago| chrono::Local::now().checked_sub_signed(ago))
+            .ok_or_else(|| format!("\"{value}\" is too far back")),
+        Err(_) => crate::rules::parse_local_time(value).map_err(|_| {
+            "expected a duration such as \"2h\" or a local time such as \"2026-10-19T09:00\""
+                .to_string()
+        }),
+    }
+}
+
+#[derive(Subcommand)]
+pub enum RulesCommand {
+    /// Show which rule would apply to a notification and what it would do
+    Test(RulesTestArgs),
+}
+
+#[derive(clap::Args)]
+pub struct RulesTestArgs {
+    #[command(flatten)]
+    pub notification: NotificationArgs,
+
+    /// Evaluate at this local time instead of now (e.g. "2026-10-19T23:30")
+    #[arg(long, value_name = "TIME", value_parser = crate::rules::parse_local_time)]
+    pub at: Option<chrono::DateTime<chrono::Local>>,
 }
 
 #[derive(Subcommand)]
@@ -108,7 +711,8 @@ pub enum ConfigCommand {
 
     /// Set a configuration value
     Set {
-        /// Configuration key (api_key, provider_key, application)
+        /// Configuration key (api_key, provider_key, application, or
+        /// profiles.<name>.<key> for a named profile)
         key: String,
         /// Configuration value
         value: String,
@@ -126,29 +730,21 @@ pub enum OutputFormat {
     Quiet,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BatchFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
 #[derive(Debug, Clone, Copy, Default, ValueEnum)]
-pub enum Priority {
-    /// Very low priority (-2)
-    VeryLow,
-    /// Moderate priority (-1)
-    Moderate,
+pub enum QrMode {
+    /// Render when stdout is a terminal wide enough to fit the code
     #[default]
-    /// Normal priority (0)
-    Normal,
-    /// High priority (1)
-    High,
-    /// Emergency priority (2)
-    Emergency,
-}
-
-impl Priority {
-    pub fn as_i8(self) -> i8 {
-        match self {
-            Priority::VeryLow => -2,
-            Priority::Moderate => -1,
-            Priority::Normal => 0,
-            Priority::High => 1,
-            Priority::Emergency => 2,
-        }
-    }
+    Auto,
+    /// Always render
+    Always,
+    /// Never render
+    Never,
 }
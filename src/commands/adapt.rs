@@ -0,0 +1,110 @@
+use std::io::Read;
+
+use crate::adapters;
+use crate::api::{ProwlClient, SendRequest};
+use crate::cli::AdaptArgs;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::output::{LogLevel, OutputFormatter, create_spinner};
+
+pub async fn execute(
+    args: &AdaptArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let payload = match args.input {
+        Some(ref path) => std::fs::read(path)?,
+        None => read_payload()?,
+    };
+
+    let template = SendRequest {
+        apikey: config.require_api_key()?.to_string(),
+        application: config.application.clone(),
+        event: "Alert".to_string(),
+        description: String::new(),
+        priority: 0,
+        url: None,
+        providerkey: config.provider_key.clone(),
+    };
+
+    send_adapted(
+        &args.adapter,
+        &payload,
+        &template,
+        config,
+        args.dry_run,
+        formatter,
+    )
+    .await
+}
+
+/// Converts `payload` with the named adapter and sends the resulting notifications, or just
+/// shows them when `dry_run` is set.
+pub async fn send_adapted(
+    adapter: &str,
+    payload: &[u8],
+    template: &SendRequest,
+    config: &ResolvedConfig,
+    dry_run: bool,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let file_config = Config::load()?;
+    let adapter = adapters::build(adapter, &file_config)?;
+    let requests = adapter.adapt(payload, template)?;
+
+    if dry_run {
+        for request in &requests {
+            formatter.format_dry_run(request);
+        }
+        return Ok(());
+    }
+
+    let client = ProwlClient::from_config(config, &file_config)?;
+    if let [request] = requests.as_slice() {
+        return send_one(&client, request, formatter).await;
+    }
+
+    // One alert failing mustn't keep the rest of the payload from going out.
+    let mut failed = 0;
+    for request in &requests {
+        if let Err(e) = send_one(&client, request, formatter).await {
+            formatter.format_log(
+                LogLevel::Error,
+                &format!("Failed to send \"{}\": {e}", request.event),
+            );
+            failed += 1;
+        }
+    }
+
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(ProwlError::PartialFailure {
+            failed,
+            total: requests.len(),
+        })
+    }
+}
+
+async fn send_one(
+    client: &ProwlClient,
+    request: &SendRequest,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let spinner = create_spinner("Sending notification...");
+    let response = delivery::send(client, request).await;
+    spinner.finish_and_clear();
+    match response? {
+        Sent::Delivered(response) => formatter.format_send_success(&response),
+        Sent::Held(notification) => formatter.format_scheduled(&notification),
+    }
+    Ok(())
+}
+
+/// Reads a whole payload from stdin.
+pub fn read_payload() -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    std::io::stdin().read_to_end(&mut payload)?;
+    Ok(payload)
+}
@@ -0,0 +1,296 @@
+use serde::Deserialize;
+use std::io::Read;
+use std::path::Path;
+
+use crate::api::{PriorityValue, ProwlClient, SendRequest};
+use crate::cli::{BatchFormat, SendArgs};
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::output::{BatchOutcome, BatchStatus, OutputFormatter};
+
+/// One notification in a batch file. Missing fields fall back to the `send` flags and config.
+#[derive(Debug, Deserialize)]
+struct BatchRecord {
+    #[serde(default)]
+    event: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    priority: Option<PriorityValue>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    recipients: Option<Recipients>,
+    #[serde(default)]
+    application: Option<String>,
+}
+
+/// Recipient API keys, either as a list or as one comma-separated string (the only option in CSV).
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Recipients {
+    Joined(String),
+    List(Vec<String>),
+}
+
+/// A parsed batch record, tagged with the line it came from.
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub line: usize,
+    pub request: Result<SendRequest>,
+}
+
+pub async fn execute(
+    path: &Path,
+    args: &SendArgs,
+    template: SendRequest,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let contents = if path == Path::new("-") {
+        let mut contents = String::new();
+        std::io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        std::fs::read_to_string(path)?
+    };
+
+    let format = args
+        .batch_format
+        .unwrap_or_else(|| BatchFormat::from_path(path));
+    // Other backends don't send to API keys, so records needn't have any.
+    let entries = parse_batch(&contents, format, &template, config.backend.is_none());
+
+    // Everything is validated before the first send so a bad record is reported up front
+    // rather than after half the batch has already gone out.
+    let mut outcomes = Vec::with_capacity(entries.len());
+    let mut pending = Vec::new();
+    for entry in entries {
+        match entry.request {
+            Ok(request) => pending.push((entry.line, request)),
+            Err(e) => {
+                let outcome = BatchOutcome::invalid(entry.line, &e);
+                formatter.format_batch_outcome(&outcome);
+                outcomes.push(outcome);
+            }
+        }
+    }
+
+    if args.dry_run {
+        // One outcome line per record, so JSON output stays JSON Lines.
+        for (line, request) in pending {
+            let outcome = BatchOutcome::valid(line, &request.event);
+            formatter.format_batch_outcome(&outcome);
+            outcomes.push(outcome);
+        }
+        return finish(outcomes, formatter);
+    }
+
+    let client = ProwlClient::from_config(config, &Config::load()?)?;
+    let mut exhausted: Option<String> = None;
+
+    for (index, (line, request)) in pending.into_iter().enumerate() {
+        let outcome = if let Some(ref reason) = exhausted {
+            BatchOutcome::failed(line, &request.event, reason)
+        } else {
+            if index > 0 {
+                tokio::time::sleep(args.pace).await;
+            }
+            match delivery::send(&client, &request).await {
+                Ok(Sent::Held(_)) => BatchOutcome::held(line, &request.event),
+                Ok(Sent::Delivered(resp)) => {
+                    if resp.remaining == Some(0) {
+                        exhausted = Some(match resp.reset_date {
+                            Some(ref reset) => format!("API rate limit exhausted until {reset}"),
+                            None => "API rate limit exhausted".to_string(),
+                        });
+                    }
+                    BatchOutcome::sent(line, &request.event, &resp)
+                }
+                Err(e) => {
+                    if let ProwlError::Api { code: 406, .. } = e {
+                        exhausted = Some(e.to_string());
+                    }
+                    BatchOutcome::failed(line, &request.event, &e.to_string())
+                }
+            }
+        };
+        formatter.format_batch_outcome(&outcome);
+        outcomes.push(outcome);
+    }
+
+    finish(outcomes, formatter)
+}
+
+/// Reports the summary, failing if any record was invalid or failed to send.
+fn finish(mut outcomes: Vec<BatchOutcome>, formatter: &dyn OutputFormatter) -> Result<()> {
+    outcomes.sort_by_key(|o| o.line);
+    formatter.format_batch_summary(&outcomes);
+
+    let failed = outcomes
+        .iter()
+        .filter(|o| matches!(o.status, BatchStatus::Invalid | BatchStatus::Failed))
+        .count();
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(ProwlError::PartialFailure {
+            failed,
+            total: outcomes.len(),
+        })
+    }
+}
+
+impl BatchFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => BatchFormat::Csv,
+            _ => BatchFormat::Jsonl,
+        }
+    }
+}
+
+/// Parses every record in `contents` into a validated request built on top of `template`.
+/// With `require_key`, records must end up with at least one recipient API key.
+pub fn parse_batch(
+    contents: &str,
+    format: BatchFormat,
+    template: &SendRequest,
+    require_key: bool,
+) -> Vec<BatchEntry> {
+    match format {
+        BatchFormat::Jsonl => contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| BatchEntry {
+                line: index + 1,
+                request: serde_json::from_str(line)
+                    .map_err(|e| ProwlError::InvalidInput(e.to_string()))
+                    .and_then(|record| build_request(record, template, require_key)),
+            })
+            .collect(),
+        BatchFormat::Csv => {
+            let mut reader = csv::Reader::from_reader(contents.as_bytes());
+            reader
+                .deserialize::<BatchRecord>()
+                .enumerate()
+                .map(|(index, record)| {
+                    // The header is line 1; fall back to counting records if the reader
+                    // couldn't tell where the failing record started.
+                    let line = match &record {
+                        Err(e) => e.position().map(|p| p.line() as usize),
+                        Ok(_) => None,
+                    };
+                    BatchEntry {
+                        line: line.unwrap_or(index + 2),
+                        request: record
+                            .map_err(|e| ProwlError::InvalidInput(e.to_string()))
+                            .and_then(|record| build_request(record, template, require_key)),
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+fn build_request(
+    record: BatchRecord,
+    template: &SendRequest,
+    require_key: bool,
+) -> Result<SendRequest> {
+    let priority = match record.priority {
+        Some(priority) => priority.as_i8()?,
+        None => template.priority,
+    };
+
+    let apikey = match record.recipients {
+        None => template.apikey.clone(),
+        Some(Recipients::Joined(keys)) => keys,
+        Some(Recipients::List(keys)) => keys.join(","),
+    };
+    if require_key && apikey.trim().is_empty() {
+        return Err(ProwlError::MissingApiKey);
+    }
+
+    let request = SendRequest {
+        apikey,
+        application: record
+            .application
+            .unwrap_or_else(|| template.application.clone()),
+        event: record.event.unwrap_or_else(|| template.event.clone()),
+        description: record
+            .description
+            .unwrap_or_else(|| template.description.clone()),
+        priority,
+        url: record.url.or_else(|| template.url.clone()),
+        providerkey: template.providerkey.clone(),
+    };
+    request.validate()?;
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> SendRequest {
+        SendRequest {
+            apikey: "default-key".to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_parse_jsonl_with_defaults_and_line_numbers() {
+        let contents = r#"{"description": "first"}
+
+{"event": "Deploy", "description": "second", "priority": "high", "recipients": ["a", "b"]}
+{"description": "third", "priority": 7}
+not json
+"#;
+        let entries = parse_batch(contents, BatchFormat::Jsonl, &template(), true);
+        let lines: Vec<usize> = entries.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 3, 4, 5]);
+
+        let first = entries[0].request.as_ref().unwrap();
+        assert_eq!(first.event, "Alert");
+        assert_eq!(first.apikey, "default-key");
+
+        let second = entries[1].request.as_ref().unwrap();
+        assert_eq!(second.event, "Deploy");
+        assert_eq!(second.priority, 1);
+        assert_eq!(second.apikey, "a,b");
+
+        assert!(matches!(
+            entries[2].request,
+            Err(ProwlError::InvalidPriority)
+        ));
+        assert!(matches!(
+            entries[3].request,
+            Err(ProwlError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let contents = "event,description,priority,url,recipients,application\n\
+            Backup,done,-1,,\"k1,k2\",\n\
+            Backup,failed,emergency,https://example.com,,cron\n";
+        let entries = parse_batch(contents, BatchFormat::Csv, &template(), true);
+        assert_eq!(entries.len(), 2);
+
+        let first = entries[0].request.as_ref().unwrap();
+        assert_eq!(entries[0].line, 2);
+        assert_eq!(first.priority, -1);
+        assert_eq!(first.apikey, "k1,k2");
+        assert_eq!(first.url, None);
+
+        let second = entries[1].request.as_ref().unwrap();
+        assert_eq!(entries[1].line, 3);
+        assert_eq!(second.priority, 2);
+        assert_eq!(second.apikey, "default-key");
+        assert_eq!(second.application, "cron");
+    }
+}
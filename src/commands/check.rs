@@ -0,0 +1,230 @@
+use chrono::Local;
+use reqwest::Client;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::api::{Priority, ProwlClient, SendRequest};
+use crate::check::{Check, CheckState, Probe, Transition};
+use crate::cli::CheckArgs;
+use crate::commands::shutdown_signal;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::output::{LogLevel, OutputFormatter, via_channel};
+
+pub async fn execute(
+    args: &CheckArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let file_config = Config::load()?;
+    let checks = select(args, Check::load_all(&file_config)?)?;
+
+    let template = SendRequest {
+        apikey: config.require_api_key()?.to_string(),
+        application: config.application.clone(),
+        event: String::new(),
+        description: String::new(),
+        priority: 0,
+        url: None,
+        providerkey: config.provider_key.clone(),
+    };
+    let client = ProwlClient::from_config(config, &file_config)?;
+    let http = Client::builder()
+        .user_agent(format!("prowl-cli/{}", env!("CARGO_PKG_VERSION")))
+        .build()?;
+
+    let Some(every) = args.every else {
+        return check_once(args, &checks, &http, &client, &template, formatter).await;
+    };
+
+    formatter.format_log(
+        LogLevel::Info,
+        &format!(
+            "Checking {} endpoint(s) every {}",
+            checks.len(),
+            humantime::format_duration(every)
+        ),
+    );
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        if let Err(e) = check_once(args, &checks, &http, &client, &template, formatter).await {
+            formatter.format_log(LogLevel::Error, &e.to_string());
+        }
+        tokio::select! {
+            () = tokio::time::sleep(every) => {}
+            _ = &mut shutdown => break,
+        }
+    }
+
+    formatter.format_log(LogLevel::Info, "Shut down");
+    Ok(())
+}
+
+/// The checks named on the command line, or every configured check if none are. Arguments
+/// that aren't configured names are URLs, checked with the command-line expectations.
+fn select(args: &CheckArgs, configured: Vec<Check>) -> Result<Vec<Check>> {
+    if args.targets.is_empty() {
+        if configured.is_empty() {
+            return Err(ProwlError::Config(
+                "No checks configured; give a URL or add a [checks.<name>] section".to_string(),
+            ));
+        }
+        return Ok(configured);
+    }
+
+    args.targets
+        .iter()
+        .map(
+            |target| match configured.iter().find(|check| check.name == *target) {
+                Some(check) => Ok(check.clone()),
+                None => Check::from_url(
+                    target,
+                    args.expect_status,
+                    args.expect_body.clone(),
+                    args.timeout,
+                ),
+            },
+        )
+        .collect()
+}
+
+/// Probes every check at once, then notifies about the ones that went down or came back
+/// since the last run. The state file isn't held locked while probing or sending.
+async fn check_once(
+    args: &CheckArgs,
+    checks: &[Check],
+    http: &Client,
+    client: &ProwlClient,
+    template: &SendRequest,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let mut probes = JoinSet::new();
+    for (index, check) in checks.iter().enumerate() {
+        let check = check.clone();
+        let http = http.clone();
+        probes.spawn(async move { (index, check.probe(&http).await) });
+    }
+    let mut results: Vec<(usize, Probe)> = probes.join_all().await;
+    results.sort_by_key(|(index, _)| *index);
+
+    let now = Local::now().timestamp();
+    let file = CheckState::open()?;
+    let transitions: Vec<_> = results
+        .into_iter()
+        .filter_map(|(index, probe)| {
+            let check = &checks[index];
+            log_probe(check, &probe, formatter);
+            let transition = file.data.transition(&check.name, probe.is_up(), now)?;
+            Some((check, probe, transition))
+        })
+        .collect();
+    drop(file);
+
+    let mut sent = Vec::new();
+    for (check, probe, transition) in transitions {
+        let request = notification(check, &probe, transition, now, template);
+        if args.dry_run {
+            formatter.format_dry_run(&request);
+            continue;
+        }
+        match delivery::send(client, &request).await {
+            Ok(Sent::Held(notification)) => {
+                formatter.format_log(LogLevel::Info, &delivery::held(&notification));
+                sent.push((check, transition));
+            }
+            Ok(Sent::Delivered(response)) => {
+                formatter.format_log(
+                    LogLevel::Success,
+                    &format!("Sent \"{}\"{}", request.event, via_channel(&response)),
+                );
+                sent.push((check, transition));
+            }
+            Err(e @ ProwlError::DroppedByRule { .. }) => {
+                formatter.format_log(LogLevel::Warning, &e.to_string());
+                sent.push((check, transition));
+            }
+            Err(e) => formatter.format_log(
+                LogLevel::Error,
+                &format!("Failed to send \"{}\", will retry: {e}", request.event),
+            ),
+        }
+    }
+
+    if !sent.is_empty() {
+        let mut file = CheckState::open()?;
+        for (check, transition) in sent {
+            file.data.record(&check.name, transition);
+        }
+        file.save()?;
+    }
+    Ok(())
+}
+
+fn log_probe(check: &Check, probe: &Probe, formatter: &dyn OutputFormatter) {
+    match &probe.failure {
+        None => formatter.format_log(
+            LogLevel::Info,
+            &format!("{}: up ({})", check.name, response_summary(probe)),
+        ),
+        Some(failure) => formatter.format_log(
+            LogLevel::Warning,
+            &format!("{}: down ({failure})", check.name),
+        ),
+    }
+}
+
+fn notification(
+    check: &Check,
+    probe: &Probe,
+    transition: Transition,
+    now: i64,
+    template: &SendRequest,
+) -> SendRequest {
+    let (event, mut description, priority) = match transition {
+        Transition::Down { .. } => (
+            format!("Down: {}", check.name),
+            match (&probe.failure, probe.status) {
+                (Some(failure), Some(_)) => {
+                    format!("{failure} after {} ms.", probe.latency.as_millis())
+                }
+                (Some(failure), None) => format!("{failure}."),
+                (None, _) => "Check failed.".to_string(),
+            },
+            Priority::High,
+        ),
+        Transition::Up { down_since } => (
+            format!("Up: {}", check.name),
+            format!(
+                "Responding again after {} down: {}.",
+                humantime::format_duration(Duration::from_secs(
+                    u64::try_from(now - down_since).unwrap_or(0)
+                )),
+                response_summary(probe),
+            ),
+            Priority::Moderate,
+        ),
+    };
+    if !probe.snippet.is_empty() {
+        description.push_str(&format!("\n\n{}", probe.snippet));
+    }
+
+    SendRequest {
+        event,
+        description,
+        priority: priority.as_i8(),
+        url: Some(check.url.to_string()),
+        ..template.clone()
+    }
+}
+
+/// "HTTP 200 in 85 ms"
+fn response_summary(probe: &Probe) -> String {
+    let status = probe.status.map_or_else(
+        || "No response".to_string(),
+        |status| format!("HTTP {status}"),
+    );
+    format!("{status} in {} ms", probe.latency.as_millis())
+}
@@ -23,9 +23,8 @@ fn init_config(force: bool, formatter: &dyn OutputFormatter) -> Result<()> {
     }
 
     let config = Config {
-        api_key: None,
-        provider_key: None,
         application: Some("prowl-cli".to_string()),
+        ..Config::default()
     };
 
     config.save()?;
@@ -42,18 +41,7 @@ fn show_config(formatter: &dyn OutputFormatter) -> Result<()> {
 
 fn set_config(key: &str, value: &str, formatter: &dyn OutputFormatter) -> Result<()> {
     let mut config = Config::load()?;
-
-    match key {
-        "api_key" => config.api_key = Some(value.to_string()),
-        "provider_key" => config.provider_key = Some(value.to_string()),
-        "application" => config.application = Some(value.to_string()),
-        _ => {
-            return Err(ProwlError::Config(format!(
-                "Unknown config key: {key}. Valid keys are: api_key, provider_key, application"
-            )));
-        }
-    }
-
+    config.set_value(key, value)?;
     config.save()?;
     formatter.format_config_set(key, value);
     Ok(())
@@ -0,0 +1,380 @@
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+
+use crate::api::{ProwlClient, SendRequest};
+use crate::cli::DaemonArgs;
+use crate::commands::shutdown_signal;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::output::{LogLevel, OutputFormatter};
+use crate::socket::{self, MAX_LINE_LEN, SocketRequest, SocketResponse};
+use crate::target;
+
+type LogSender = mpsc::UnboundedSender<(LogLevel, String)>;
+
+struct DaemonState {
+    client: ProwlClient,
+    template: SendRequest,
+    allow_uids: Vec<u32>,
+    allow_gids: Vec<u32>,
+}
+
+impl DaemonState {
+    /// Whether a client running as `uid` with primary group `gid` may submit. Peer
+    /// credentials carry only the primary group, so supplementary groups can't grant access.
+    fn allows(&self, uid: u32, gid: u32) -> bool {
+        self.allow_uids.contains(&uid) || self.allow_gids.contains(&gid)
+    }
+}
+
+pub async fn execute(
+    args: &DaemonArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let template = SendRequest {
+        apikey: config.require_api_key()?.to_string(),
+        application: config.application.clone(),
+        event: "Alert".to_string(),
+        description: String::new(),
+        priority: 0,
+        url: None,
+        providerkey: config.provider_key.clone(),
+    };
+
+    let file_config = Config::load()?;
+    let daemon_config = file_config.daemon.clone().unwrap_or_default();
+    let path = socket::socket_path(args.socket.as_deref(), &file_config);
+    let listener = bind(&path).await?;
+
+    // The daemon's own user may always submit; the socket file is owned by it.
+    let mut allow_uids = daemon_config.allow_uids;
+    allow_uids.push(std::fs::metadata(&path)?.uid());
+
+    let state = Arc::new(DaemonState {
+        client: ProwlClient::from_config(config, &file_config)?,
+        template,
+        allow_uids,
+        allow_gids: daemon_config.allow_gids,
+    });
+
+    formatter.format_log(LogLevel::Info, &format!("Listening on {}", path.display()));
+
+    // Connection tasks can't borrow the formatter, so their log lines come back here.
+    let (log, mut logs) = mpsc::unbounded_channel();
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    let result = loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle(stream, state.clone(), log.clone()));
+                }
+                Err(e) => break Err(e.into()),
+            },
+            Some((level, line)) = logs.recv() => formatter.format_log(level, &line),
+            _ = &mut shutdown => break Ok(()),
+        }
+    };
+
+    let _ = std::fs::remove_file(&path);
+    formatter.format_log(LogLevel::Info, "Shut down");
+    result
+}
+
+/// Binds the socket, replacing a stale socket left by a daemon that didn't shut down
+/// cleanly, and opens it to every local user; peer credentials decide who is actually served.
+/// Anything else already at `path` is left alone.
+async fn bind(path: &Path) -> Result<UnixListener> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(ProwlError::Daemon(format!(
+                "{} exists and isn't a socket; refusing to replace it",
+                path.display()
+            )));
+        }
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(ProwlError::Daemon(format!(
+                "Another daemon is already listening on {}",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o666))?;
+    Ok(listener)
+}
+
+async fn handle(stream: UnixStream, state: Arc<DaemonState>, log: LogSender) {
+    let Ok(credentials) = stream.peer_cred() else {
+        return;
+    };
+    let (uid, gid) = (credentials.uid(), credentials.gid());
+    let (reader, mut writer) = stream.into_split();
+
+    if !state.allows(uid, gid) {
+        let _ = log.send((
+            LogLevel::Warning,
+            format!("Refused connection from uid {uid} (gid {gid})"),
+        ));
+        let denied = ProwlError::Daemon("Permission denied".to_string());
+        let _ = write_response(&mut writer, &SocketResponse::error(&denied)).await;
+        return;
+    }
+
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match (&mut reader).take(MAX_LINE_LEN).read_line(&mut line).await {
+            Ok(0) | Err(_) => return,
+            // The rest of the line would read as another request; give up on the connection.
+            Ok(read) if read as u64 == MAX_LINE_LEN && !line.ends_with('\n') => {
+                let too_long = ProwlError::InvalidInput(format!(
+                    "Request too long (max {MAX_LINE_LEN} bytes)"
+                ));
+                let _ = write_response(&mut writer, &SocketResponse::error(&too_long)).await;
+                return;
+            }
+            Ok(_) if line.trim().is_empty() => continue,
+            Ok(_) => {}
+        }
+
+        let request = serde_json::from_str::<SocketRequest>(&line)
+            .map_err(|e| ProwlError::InvalidInput(e.to_string()))
+            .and_then(|request| build_request(request, &state.template));
+
+        let response = match request {
+            Ok(request) => {
+                let result = delivery::send(&state.client, &request).await;
+                let _ = log.send(match result {
+                    Ok(Sent::Delivered(_)) => (
+                        LogLevel::Success,
+                        format!("Delivered \"{}\" for uid {uid}", request.event),
+                    ),
+                    Ok(Sent::Held(ref notification)) => (
+                        LogLevel::Info,
+                        format!("{} for uid {uid}", delivery::held(notification)),
+                    ),
+                    Err(ref e) => (
+                        LogLevel::Error,
+                        format!("Failed to deliver \"{}\" for uid {uid}: {e}", request.event),
+                    ),
+                });
+                match result {
+                    Ok(Sent::Delivered(response)) => SocketResponse::from_result(&Ok(response)),
+                    // Accepted; the daemon's `prowl schedule run` sends it later.
+                    Ok(Sent::Held(notification)) => SocketResponse::held(&notification),
+                    Err(e) => SocketResponse::error(&e),
+                }
+            }
+            Err(e) => SocketResponse::error(&e),
+        };
+
+        if write_response(&mut writer, &response).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn write_response(
+    writer: &mut tokio::net::unix::OwnedWriteHalf,
+    response: &SocketResponse,
+) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(response).map_err(std::io::Error::other)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await
+}
+
+fn build_request(request: SocketRequest, template: &SendRequest) -> Result<SendRequest> {
+    let priority = match request.priority {
+        Some(priority) => priority.as_i8()?,
+        None => template.priority,
+    };
+
+    let mut apikey = template.apikey.clone();
+    for key in target::recipient_keys(&request.to)? {
+        apikey.push(',');
+        apikey.push_str(&key);
+    }
+
+    let request = SendRequest {
+        apikey,
+        application: request
+            .application
+            .unwrap_or_else(|| template.application.clone()),
+        event: request.event.unwrap_or_else(|| template.event.clone()),
+        description: request.description,
+        priority,
+        url: request.url,
+        providerkey: template.providerkey.clone(),
+    };
+    request.validate()?;
+    Ok(request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::PriorityValue;
+    use crate::notifiers::stand_in::Recorder;
+
+    fn template() -> SendRequest {
+        SendRequest {
+            apikey: "a".repeat(40),
+            application: "prowl-daemon".to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_build_request() {
+        let other = "b".repeat(40);
+        let request = build_request(
+            SocketRequest {
+                description: "Backup finished".to_string(),
+                priority: Some(PriorityValue::Name("high".to_string())),
+                to: vec![other.clone()],
+                ..SocketRequest::default()
+            },
+            &template(),
+        )
+        .unwrap();
+        assert_eq!(request.apikey, format!("{},{other}", "a".repeat(40)));
+        assert_eq!(request.application, "prowl-daemon");
+        assert_eq!(request.event, "Alert");
+        assert_eq!(request.description, "Backup finished");
+        assert_eq!(request.priority, 1);
+
+        let invalid = SocketRequest {
+            priority: Some(PriorityValue::Level(5)),
+            ..SocketRequest::default()
+        };
+        assert!(build_request(invalid, &template()).is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let state = DaemonState {
+            client: ProwlClient::new().unwrap(),
+            template: template(),
+            allow_uids: vec![1000, 1001],
+            allow_gids: vec![850],
+        };
+        assert!(state.allows(1001, 100));
+        assert!(state.allows(1002, 850));
+        assert!(!state.allows(1002, 100));
+    }
+
+    #[tokio::test]
+    async fn test_submit_round_trip() {
+        let dir = std::env::temp_dir().join(format!("prowl-daemon-{}", std::process::id()));
+        let path = dir.join("prowl.sock");
+        let listener = bind(&path).await.unwrap();
+        let uid = std::fs::metadata(&path).unwrap().uid();
+
+        // Neither a live daemon's socket nor a file that isn't a socket is replaced.
+        let error = bind(&path).await.unwrap_err().to_string();
+        assert!(error.contains("already listening"), "{error}");
+        drop(listener.accept().await.unwrap());
+        let file = dir.join("not-a-socket");
+        std::fs::write(&file, "keep me").unwrap();
+        let error = bind(&file).await.unwrap_err().to_string();
+        assert!(error.contains("isn't a socket"), "{error}");
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+
+        let recorder = Arc::new(Recorder::default());
+        let client = ProwlClient::new()
+            .unwrap()
+            .with_backend(Some(recorder.clone()));
+        let allowed = Arc::new(DaemonState {
+            client: client.clone(),
+            template: template(),
+            allow_uids: vec![uid],
+            allow_gids: Vec::new(),
+        });
+        let refused = Arc::new(DaemonState {
+            client,
+            template: template(),
+            allow_uids: Vec::new(),
+            allow_gids: Vec::new(),
+        });
+        let (log, _logs) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for state in [allowed.clone(), allowed, refused] {
+                let (stream, _) = listener.accept().await.unwrap();
+                handle(stream, state, log.clone()).await;
+            }
+        });
+
+        let request = SocketRequest {
+            event: Some("Backup finished".to_string()),
+            description: "3.2 GB in 4m".to_string(),
+            ..SocketRequest::default()
+        };
+        let Sent::Delivered(response) = socket::submit(&path, &request).await.unwrap() else {
+            panic!("expected the notification to be delivered");
+        };
+        assert_eq!(response.code, 200);
+        let received = recorder.received();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event, "Backup finished");
+        assert_eq!(received[0].apikey, "a".repeat(40));
+
+        // An overlong line gets one error, not one per chunk, and the connection closes.
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        let mut overlong = "x".repeat(MAX_LINE_LEN as usize + 100);
+        overlong.push('\n');
+        let _ = stream.write_all(overlong.as_bytes()).await;
+        let mut replies = BufReader::new(stream);
+        let mut reply = String::new();
+        replies.read_line(&mut reply).await.unwrap();
+        assert!(reply.contains("Request too long"), "{reply}");
+        reply.clear();
+        assert!(
+            matches!(replies.read_line(&mut reply).await, Ok(0) | Err(_)),
+            "{reply}"
+        );
+
+        let error = socket::submit(&path, &request).await.unwrap_err();
+        assert!(error.to_string().contains("Permission denied"), "{error}");
+        assert_eq!(recorder.received().len(), 1);
+
+        // A notification a rule held back comes back as held, not delivered.
+        let held_path = dir.join("held.sock");
+        let held_listener = bind(&held_path).await.unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = held_listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut line = String::new();
+            BufReader::new(reader).read_line(&mut line).await.unwrap();
+            let response = SocketResponse {
+                success: true,
+                scheduled: Some(7),
+                scheduled_for: Some(1_800_000_000),
+                ..SocketResponse::default()
+            };
+            write_response(&mut writer, &response).await.unwrap();
+        });
+        let Sent::Held(notification) = socket::submit(&held_path, &request).await.unwrap() else {
+            panic!("expected the notification to be held");
+        };
+        assert_eq!(notification.id, 7);
+        assert_eq!(notification.due, 1_800_000_000);
+        assert_eq!(notification.request.event, "Backup finished");
+        assert!(notification.request.apikey.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
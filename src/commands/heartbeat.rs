@@ -0,0 +1,198 @@
+use chrono::Local;
+
+use crate::api::{Priority, ProwlClient, SendRequest};
+use crate::cli::{HeartbeatCheckArgs, HeartbeatCommand};
+use crate::commands::shutdown_signal;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::heartbeat::{Heartbeat, HeartbeatState, Transition};
+use crate::output::{LogLevel, OutputFormatter, via_channel};
+use crate::rules::local_time;
+
+pub async fn execute(
+    cmd: &HeartbeatCommand,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let file_config = Config::load()?;
+    let heartbeats = Heartbeat::load_all(&file_config)?;
+
+    match cmd {
+        HeartbeatCommand::Ping { name } => {
+            if !heartbeats.iter().any(|heartbeat| heartbeat.name == *name) {
+                return Err(ProwlError::InvalidInput(format!(
+                    "Unknown heartbeat \"{name}\"; configure it under [heartbeats.{name}]"
+                )));
+            }
+            let mut file = HeartbeatState::open()?;
+            file.data.ping(name, Local::now());
+            file.save()?;
+            formatter.format_heartbeat_ping(name);
+            Ok(())
+        }
+        HeartbeatCommand::List => {
+            let file = HeartbeatState::open()?;
+            let statuses: Vec<_> = heartbeats
+                .iter()
+                .map(|heartbeat| file.data.status(heartbeat))
+                .collect();
+            formatter.format_heartbeat_list(&statuses);
+            Ok(())
+        }
+        HeartbeatCommand::Check(args) => {
+            if heartbeats.is_empty() {
+                return Err(ProwlError::Config(
+                    "No heartbeats configured; add a [heartbeats.<name>] section".to_string(),
+                ));
+            }
+            let template = SendRequest {
+                apikey: config.require_api_key()?.to_string(),
+                application: config.application.clone(),
+                event: String::new(),
+                description: String::new(),
+                priority: 0,
+                url: None,
+                providerkey: config.provider_key.clone(),
+            };
+            let client = ProwlClient::from_config(config, &file_config)?;
+            check(args, &heartbeats, &client, &template, formatter).await
+        }
+    }
+}
+
+async fn check(
+    args: &HeartbeatCheckArgs,
+    heartbeats: &[Heartbeat],
+    client: &ProwlClient,
+    template: &SendRequest,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    if !args.watch {
+        return check_once(args, heartbeats, client, template, formatter).await;
+    }
+
+    formatter.format_log(
+        LogLevel::Info,
+        &format!(
+            "Checking {} heartbeat(s) every {}",
+            heartbeats.len(),
+            humantime::format_duration(args.interval)
+        ),
+    );
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        if let Err(e) = check_once(args, heartbeats, client, template, formatter).await {
+            formatter.format_log(LogLevel::Error, &e.to_string());
+        }
+        tokio::select! {
+            () = tokio::time::sleep(args.interval) => {}
+            _ = &mut shutdown => break,
+        }
+    }
+
+    formatter.format_log(LogLevel::Info, "Shut down");
+    Ok(())
+}
+
+/// Sends a notification for each heartbeat that went missing or came back since the last
+/// check. The state file isn't held locked while sending, so pings aren't kept waiting.
+async fn check_once(
+    args: &HeartbeatCheckArgs,
+    heartbeats: &[Heartbeat],
+    client: &ProwlClient,
+    template: &SendRequest,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let now = Local::now();
+    let mut file = HeartbeatState::open()?;
+    let transitions: Vec<_> = heartbeats
+        .iter()
+        .filter_map(|heartbeat| {
+            file.data.watch(heartbeat, now);
+            let transition = file.data.transition(heartbeat, now)?;
+            Some((heartbeat, transition))
+        })
+        .collect();
+    file.save()?;
+
+    let mut sent = Vec::new();
+    for (heartbeat, transition) in transitions {
+        let request = notification(heartbeat, transition, template);
+        if args.dry_run {
+            formatter.format_dry_run(&request);
+            continue;
+        }
+        match delivery::send(client, &request).await {
+            Ok(Sent::Held(notification)) => {
+                formatter.format_log(LogLevel::Info, &delivery::held(&notification));
+                sent.push((heartbeat, transition));
+            }
+            Ok(Sent::Delivered(response)) => {
+                formatter.format_log(
+                    LogLevel::Success,
+                    &format!("Sent \"{}\"{}", request.event, via_channel(&response)),
+                );
+                sent.push((heartbeat, transition));
+            }
+            Err(e @ ProwlError::DroppedByRule { .. }) => {
+                formatter.format_log(LogLevel::Warning, &e.to_string());
+                sent.push((heartbeat, transition));
+            }
+            Err(e) => formatter.format_log(
+                LogLevel::Error,
+                &format!("Failed to send \"{}\", will retry: {e}", request.event),
+            ),
+        }
+    }
+
+    if !sent.is_empty() {
+        let mut file = HeartbeatState::open()?;
+        for (heartbeat, transition) in sent {
+            file.data.record(&heartbeat.name, transition);
+        }
+        file.save()?;
+    }
+    Ok(())
+}
+
+fn notification(
+    heartbeat: &Heartbeat,
+    transition: Transition,
+    template: &SendRequest,
+) -> SendRequest {
+    let format_time = |timestamp| local_time(timestamp).format("%a %Y-%m-%d %H:%M");
+    let (event, description, priority) = match transition {
+        Transition::Missed { last_ping, .. } => (
+            format!("Heartbeat missed: {}", heartbeat.name),
+            format!(
+                "{}; expected every {} (plus {} grace).",
+                last_ping.map_or_else(
+                    || "Never pinged".to_string(),
+                    |time| format!("Last ping {}", format_time(time)),
+                ),
+                humantime::format_duration(heartbeat.interval),
+                humantime::format_duration(heartbeat.grace),
+            ),
+            heartbeat.priority,
+        ),
+        Transition::Recovered { down_since, pinged } => (
+            format!("Heartbeat recovered: {}", heartbeat.name),
+            format!(
+                "Pinged at {} after being overdue since {}.",
+                format_time(pinged),
+                format_time(down_since),
+            ),
+            Priority::Normal,
+        ),
+    };
+
+    SendRequest {
+        event,
+        description,
+        priority: priority.as_i8(),
+        ..template.clone()
+    }
+}
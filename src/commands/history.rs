@@ -0,0 +1,18 @@
+use crate::cli::HistoryArgs;
+use crate::error::Result;
+use crate::history::{self, HistoryFilter};
+use crate::output::OutputFormatter;
+
+pub fn execute(args: &HistoryArgs, formatter: &dyn OutputFormatter) -> Result<()> {
+    let filter = HistoryFilter {
+        since: args.since,
+        status: args.status,
+        event: args.event.clone(),
+    };
+    let mut entries = history::read(&filter)?;
+    if args.limit > 0 && entries.len() > args.limit {
+        entries.drain(..entries.len() - args.limit);
+    }
+    formatter.format_history(&entries);
+    Ok(())
+}
@@ -1,5 +1,37 @@
+pub mod adapt;
+pub mod batch;
+pub mod check;
 pub mod config_cmd;
+#[cfg(unix)]
+pub mod daemon;
+pub mod heartbeat;
+pub mod history;
 pub mod register;
+pub mod rules;
+pub mod schedule;
 pub mod send;
+pub mod sendmail;
+pub mod serve;
+pub mod smtp;
+pub mod syslog;
 pub mod token;
+pub mod users;
 pub mod verify;
+pub mod wait;
+
+/// Resolves when the process is asked to stop: Ctrl-C, or SIGTERM from a service manager
+/// such as systemd.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
@@ -1,11 +1,16 @@
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 
-use crate::api::{ProwlClient, RegisterRequest};
+use crate::api::{ApiResponse, ProwlClient, RegisterRequest, TokenRequest};
 use crate::cli::RegisterArgs;
-use crate::config::ResolvedConfig;
-use crate::error::Result;
-use crate::output::OutputFormatter;
+use crate::config::{Config, ResolvedConfig};
+use crate::error::{ProwlError, Result};
+use crate::output::{OutputFormatter, create_spinner};
+use crate::registry::KeyRegistry;
+use crate::target::mask;
+
+const INITIAL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(15);
 
 pub async fn execute(
     args: &RegisterArgs,
@@ -13,37 +18,194 @@ pub async fn execute(
     formatter: &dyn OutputFormatter,
 ) -> Result<()> {
     let provider_key = config.require_provider_key()?;
+    let client = ProwlClient::new()?;
 
-    let request = RegisterRequest {
-        providerkey: provider_key.to_string(),
-        token: args.token.clone(),
+    let response = if args.interactive {
+        register_interactive(args, provider_key, &client, formatter).await?
+    } else {
+        let request = RegisterRequest {
+            providerkey: provider_key.to_string(),
+            token: args.token.clone().unwrap_or_default(),
+        };
+
+        let spinner = create_spinner("Retrieving API key...");
+        let response = client.retrieve_apikey(&request).await;
+        spinner.finish_and_clear();
+        response?
     };
 
-    let spinner = create_spinner("Retrieving API key...");
+    formatter.format_register_success(&response);
 
-    let client = ProwlClient::new()?;
-    let response = client.retrieve_apikey(&request).await;
+    if let Some(ref apikey) = response.apikey
+        && (args.save || args.save_profile.is_some())
+    {
+        let key = match args.save_profile {
+            Some(ref name) => format!("profiles.{name}.api_key"),
+            None => "api_key".to_string(),
+        };
+        let mut file_config = Config::load()?;
+        file_config.set_value(&key, apikey)?;
+        file_config.save()?;
+        formatter.format_config_set(&key, &mask(apikey));
+    }
+
+    if let (Some(apikey), Some(label)) = (&response.apikey, &args.label) {
+        let mut registry = KeyRegistry::open()?;
+        registry.data.insert(label, apikey);
+        registry.save()?;
+        formatter.format_users_update("add", label);
+    }
+
+    Ok(())
+}
+
+async fn register_interactive(
+    args: &RegisterArgs,
+    provider_key: &str,
+    client: &ProwlClient,
+    formatter: &dyn OutputFormatter,
+) -> Result<ApiResponse> {
+    let spinner = create_spinner("Retrieving registration token...");
+    let token_response = client
+        .retrieve_token(&TokenRequest {
+            providerkey: provider_key.to_string(),
+        })
+        .await;
+    spinner.finish_and_clear();
+    let token_response = token_response?;
+
+    formatter.format_register_pending(&token_response, args.qr.mode);
+
+    let (Some(token), Some(url)) = (&token_response.token, &token_response.token_url) else {
+        return Err(ProwlError::Api {
+            code: 500,
+            message: "Invalid retrieve response".to_string(),
+        });
+    };
 
+    if args.open {
+        open_in_browser(url);
+    }
+
+    let request = RegisterRequest {
+        providerkey: provider_key.to_string(),
+        token: token.clone(),
+    };
+
+    let spinner = create_spinner("Waiting for approval...");
+    let result = await_approval(
+        || client.retrieve_apikey(&request),
+        args.timeout,
+        INITIAL_POLL_INTERVAL,
+    )
+    .await;
     spinner.finish_and_clear();
+    result
+}
+
+/// Calls `poll` until the token is approved or anything but `TokenNotApproved` comes back,
+/// waiting `interval` between calls and half as long again each time, up to
+/// [`MAX_POLL_INTERVAL`]. Gives up with `TokenNotApproved` after `timeout`.
+async fn await_approval<F, Fut>(
+    mut poll: F,
+    timeout: Duration,
+    mut interval: Duration,
+) -> Result<ApiResponse>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<ApiResponse>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        match poll().await {
+            Err(ProwlError::TokenNotApproved) => {}
+            other => return other,
+        }
 
-    match response {
-        Ok(resp) => {
-            formatter.format_register_success(&resp);
-            Ok(())
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(ProwlError::TokenNotApproved);
         }
-        Err(e) => Err(e),
+        tokio::time::sleep(interval.min(deadline - now)).await;
+        interval = (interval * 3 / 2).min(MAX_POLL_INTERVAL);
     }
 }
 
-fn create_spinner(message: &str) -> ProgressBar {
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.set_message(message.to_string());
-    spinner.enable_steady_tick(Duration::from_millis(80));
-    spinner
+/// Best-effort attempt to open `url` with the platform's default handler.
+fn open_in_browser(url: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let _ = command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    const INTERVAL: Duration = Duration::from_millis(10);
+
+    #[tokio::test]
+    async fn test_await_approval() {
+        // Keeps polling while the token isn't approved yet.
+        let calls = Cell::new(0);
+        let response = await_approval(
+            || {
+                calls.set(calls.get() + 1);
+                let result = if calls.get() < 3 {
+                    Err(ProwlError::TokenNotApproved)
+                } else {
+                    Ok(ApiResponse::success(200, None, None))
+                };
+                async { result }
+            },
+            Duration::from_secs(10),
+            INTERVAL,
+        )
+        .await;
+        assert_eq!(response.unwrap().code, 200);
+        assert_eq!(calls.get(), 3);
+
+        // Stops at the first other error.
+        let calls = Cell::new(0);
+        let response = await_approval(
+            || {
+                calls.set(calls.get() + 1);
+                async {
+                    Err(ProwlError::Api {
+                        code: 401,
+                        message: "Invalid provider key".to_string(),
+                    })
+                }
+            },
+            Duration::from_secs(10),
+            INTERVAL,
+        )
+        .await;
+        assert!(matches!(response, Err(ProwlError::Api { code: 401, .. })));
+        assert_eq!(calls.get(), 1);
+
+        // Gives up once the timeout has passed.
+        let started = Instant::now();
+        let response = await_approval(
+            || async { Err(ProwlError::TokenNotApproved) },
+            Duration::from_millis(50),
+            INTERVAL,
+        )
+        .await;
+        assert!(matches!(response, Err(ProwlError::TokenNotApproved)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
 }
@@ -0,0 +1,33 @@
+use crate::api::SendRequest;
+use crate::cli::RulesCommand;
+use crate::commands::send::read_description;
+use crate::config::ResolvedConfig;
+use crate::error::Result;
+use crate::output::OutputFormatter;
+use crate::rules::Rules;
+
+pub fn execute(
+    cmd: &RulesCommand,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    match cmd {
+        RulesCommand::Test(args) => {
+            let notification = &args.notification;
+            let request = SendRequest {
+                apikey: config.api_key.clone().unwrap_or_default(),
+                application: config.application.clone(),
+                event: notification.event.clone(),
+                description: read_description(notification)?,
+                priority: notification.priority(config).as_i8(),
+                url: notification.url.clone(),
+                providerkey: config.provider_key.clone(),
+            };
+
+            let at = args.at.unwrap_or_else(chrono::Local::now);
+            let verdict = Rules::load()?.evaluate(&request, at);
+            formatter.format_rule_verdict(&verdict, at);
+            Ok(())
+        }
+    }
+}
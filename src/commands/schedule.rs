@@ -0,0 +1,222 @@
+use chrono::{DateTime, Local};
+use std::time::Duration;
+
+use crate::adapters::truncate;
+use crate::api::{ProwlClient, SendRequest};
+use crate::cli::{ScheduleCommand, ScheduleRunArgs};
+use crate::commands::shutdown_signal;
+use crate::config::{Config, MissedPolicy, ResolvedConfig};
+use crate::delivery;
+use crate::error::{ProwlError, Result};
+use crate::output::{LogLevel, OutputFormatter, via_channel};
+use crate::schedule::{ScheduleStore, ScheduledNotification};
+
+/// How late a notification may be before the missed policy applies, unless configured.
+const DEFAULT_GRACE: Duration = Duration::from_secs(60 * 60);
+
+pub async fn execute(
+    cmd: &ScheduleCommand,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    match cmd {
+        ScheduleCommand::List => {
+            let file = ScheduleStore::open()?;
+            formatter.format_schedule_list(&file.data.notifications);
+            Ok(())
+        }
+        ScheduleCommand::Cancel { ids } => {
+            let mut file = ScheduleStore::open()?;
+            for id in ids {
+                if !file.data.cancel(*id) {
+                    return Err(ProwlError::InvalidInput(format!(
+                        "No scheduled notification #{id}"
+                    )));
+                }
+            }
+            file.save()?;
+            formatter.format_schedule_cancel(ids);
+            Ok(())
+        }
+        ScheduleCommand::Run(args) => run(args, config, formatter).await,
+    }
+}
+
+async fn run(
+    args: &ScheduleRunArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let file_config = Config::load()?;
+    let schedule_config = file_config.schedule.clone().unwrap_or_default();
+
+    let grace = match (args.grace, schedule_config.grace.as_deref()) {
+        (Some(grace), _) => grace,
+        (None, Some(grace)) => humantime::parse_duration(grace).map_err(|e| {
+            ProwlError::Config(format!("Invalid [schedule] grace \"{grace}\": {e}"))
+        })?,
+        (None, None) => DEFAULT_GRACE,
+    };
+    let grace = chrono::Duration::from_std(grace)
+        .map_err(|_| ProwlError::InvalidInput("Grace period is too long".to_string()))?;
+    let missed = args.missed.or(schedule_config.missed).unwrap_or_default();
+
+    let client = ProwlClient::from_config(config, &file_config)?;
+
+    if !args.watch {
+        send_due(&client, grace, missed, formatter).await?;
+        return delivery::flush_summaries(&client, formatter).await;
+    }
+
+    formatter.format_log(
+        LogLevel::Info,
+        &format!(
+            "Checking for due notifications every {}",
+            humantime::format_duration(args.interval)
+        ),
+    );
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        if let Err(e) = send_due(&client, grace, missed, formatter).await {
+            formatter.format_log(LogLevel::Error, &e.to_string());
+        }
+        if let Err(e) = delivery::flush_summaries(&client, formatter).await {
+            formatter.format_log(LogLevel::Error, &e.to_string());
+        }
+        tokio::select! {
+            () = tokio::time::sleep(args.interval) => {}
+            _ = &mut shutdown => break,
+        }
+    }
+
+    formatter.format_log(LogLevel::Info, "Shut down");
+    Ok(())
+}
+
+/// What became of one due notification.
+enum Outcome {
+    /// Sent, dropped or skipped; take it off the schedule.
+    Done,
+    /// A rule held it back until then.
+    Held(DateTime<Local>),
+    /// It failed in a way worth retrying on the next pass.
+    Retry,
+}
+
+/// Sends every notification that is due. The schedule is only locked to claim them and
+/// then to record the outcomes, so slow sends don't hold up `prowl send --at`.
+async fn send_due(
+    client: &ProwlClient,
+    grace: chrono::Duration,
+    missed: MissedPolicy,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let now = Local::now();
+    let claimed = {
+        let mut file = ScheduleStore::open()?;
+        let claimed = file.data.claim_due(now);
+        if claimed.is_empty() {
+            return Ok(());
+        }
+        file.save()?;
+        claimed
+    };
+
+    let mut outcomes = Vec::with_capacity(claimed.len());
+    for notification in claimed {
+        let id = notification.id;
+        outcomes.push((
+            id,
+            send_one(client, notification, now, grace, missed, formatter).await,
+        ));
+    }
+
+    let mut file = ScheduleStore::open()?;
+    for (id, outcome) in outcomes {
+        match outcome {
+            Outcome::Done => {
+                file.data.cancel(id);
+            }
+            Outcome::Held(until) => file.data.hold(id, until),
+            Outcome::Retry => file.data.release(id),
+        }
+    }
+    file.save()
+}
+
+async fn send_one(
+    client: &ProwlClient,
+    notification: ScheduledNotification,
+    now: DateTime<Local>,
+    grace: chrono::Duration,
+    missed: MissedPolicy,
+    formatter: &dyn OutputFormatter,
+) -> Outcome {
+    let id = notification.id;
+    let mut request = notification.request.clone();
+
+    if now - notification.send_at() > grace {
+        let due = notification.due();
+        match missed {
+            MissedPolicy::Skip => {
+                formatter.format_log(
+                    LogLevel::Warning,
+                    &format!(
+                        "Skipped #{id} \"{}\", missed by more than the grace period (due {})",
+                        request.event,
+                        due.format("%Y-%m-%d %H:%M")
+                    ),
+                );
+                return Outcome::Done;
+            }
+            MissedPolicy::Send => note_missed(&mut request, due),
+        }
+    }
+
+    match client.send(&request).await {
+        Ok(response) => {
+            formatter.format_log(
+                LogLevel::Success,
+                &format!("Sent #{id} \"{}\"{}", request.event, via_channel(&response)),
+            );
+            Outcome::Done
+        }
+        Err(ProwlError::Delayed { until, .. }) => {
+            formatter.format_log(
+                LogLevel::Info,
+                &format!(
+                    "Held #{id} \"{}\" until {} by rule",
+                    request.event,
+                    until.format("%Y-%m-%d %H:%M")
+                ),
+            );
+            Outcome::Held(until)
+        }
+        Err(e) if e.is_transient() => {
+            formatter.format_log(
+                LogLevel::Warning,
+                &format!("Will retry #{id} \"{}\": {e}", request.event),
+            );
+            Outcome::Retry
+        }
+        Err(e) => {
+            formatter.format_log(
+                LogLevel::Error,
+                &format!("Dropped #{id} \"{}\": {e}", request.event),
+            );
+            Outcome::Done
+        }
+    }
+}
+
+fn note_missed(request: &mut SendRequest, due: chrono::DateTime<Local>) {
+    if !request.description.is_empty() {
+        request.description.push_str("\n\n");
+    }
+    request
+        .description
+        .push_str(&format!("(Scheduled for {})", due.format("%Y-%m-%d %H:%M")));
+    truncate(&mut request.description, SendRequest::MAX_DESCRIPTION_LEN);
+}
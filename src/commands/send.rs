@@ -1,56 +1,204 @@
-use indicatif::{ProgressBar, ProgressStyle};
+use chrono::Local;
 use std::io::{self, BufRead};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use crate::api::{ProwlClient, SendRequest};
-use crate::cli::SendArgs;
+use crate::cli::{NotificationArgs, SendArgs};
+use crate::commands::{adapt, batch};
 use crate::config::ResolvedConfig;
-use crate::error::Result;
-use crate::output::OutputFormatter;
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::output::{OutputFormatter, create_spinner};
+use crate::schedule::ScheduleStore;
+use crate::target;
+use crate::throttle::{self, Decision, ThrottleState};
+#[cfg(unix)]
+use crate::{
+    api::PriorityValue,
+    config::Config,
+    socket::{self, SocketRequest},
+};
 
 pub async fn execute(
     args: &SendArgs,
     config: &ResolvedConfig,
     formatter: &dyn OutputFormatter,
 ) -> Result<()> {
-    let description = if args.message == "-" {
-        read_stdin()?
+    let notification = &args.notification;
+    let description = if args.batch.is_some() || args.from_json {
+        String::new()
     } else {
-        args.message.clone()
+        read_description(notification)?
     };
 
-    let mut api_keys = vec![config.require_api_key()?.to_string()];
-    api_keys.extend(args.to.iter().cloned());
+    #[cfg(unix)]
+    if args.via_socket {
+        return send_via_socket(args, description, config, formatter).await;
+    }
+
+    // Batch records may name their own recipients, so the default key is only required
+    // for records that don't.
+    let mut api_keys = if args.batch.is_some() {
+        config.api_key.iter().cloned().collect()
+    } else {
+        vec![config.require_api_key()?.to_string()]
+    };
+    api_keys.extend(target::recipient_keys(&args.to)?);
     let combined_keys = api_keys.join(",");
 
     let request = SendRequest {
         apikey: combined_keys,
         application: config.application.clone(),
-        event: args.event.clone(),
+        event: notification.event.clone(),
         description,
-        priority: args.priority.as_i8(),
-        url: args.url.clone(),
+        priority: notification.priority(config).as_i8(),
+        url: notification.url.clone(),
         providerkey: config.provider_key.clone(),
     };
 
+    if let Some(ref path) = args.batch {
+        return batch::execute(path, args, request, config, formatter).await;
+    }
+
+    if let Some(ref adapter) = args.adapter {
+        let payload = adapt::read_payload()?;
+        return adapt::send_adapted(adapter, &payload, &request, config, args.dry_run, formatter)
+            .await;
+    }
+
     if args.dry_run {
         formatter.format_dry_run(&request);
         return Ok(());
     }
 
+    let due = match (args.at, args.in_) {
+        (Some(at), _) => Some(at),
+        (None, Some(delay)) => Some(
+            Local::now()
+                + chrono::Duration::from_std(delay)
+                    .map_err(|_| ProwlError::InvalidInput("--in is too far ahead".to_string()))?,
+        ),
+        (None, None) => None,
+    };
+    if let Some(due) = due {
+        request.validate()?;
+        let mut file = ScheduleStore::open()?;
+        let notification = file.data.add(due, request);
+        file.save()?;
+        formatter.format_scheduled(&notification);
+        return Ok(());
+    }
+
+    let client = ProwlClient::from_config(config, &Config::load()?)?;
+
+    let dedup_key = match args.throttle {
+        Some(window) => {
+            let key = args
+                .dedup_key
+                .clone()
+                .unwrap_or_else(|| throttle::dedup_key(&request.event, &request.description));
+            let summary = args.summary.then(|| request.clone());
+            if !check_throttle(&client, &key, window, summary, formatter).await? {
+                return Ok(());
+            }
+            Some(key)
+        }
+        None => None,
+    };
+
     let spinner = create_spinner("Sending notification...");
 
-    let client = ProwlClient::new()?;
-    let response = client.send(&request).await;
+    let response = delivery::send(&client, &request).await;
 
     spinner.finish_and_clear();
 
     match response {
-        Ok(resp) => {
-            formatter.format_send_success(&resp);
+        Ok(Sent::Held(notification)) => {
+            formatter.format_scheduled(&notification);
             Ok(())
         }
-        Err(e) => Err(e),
+        Ok(Sent::Delivered(resp)) => {
+            formatter.format_send_success(&resp);
+            // Delivered, but through a fallback: exit non-zero so scripts can tell.
+            match resp.channel {
+                Some(channel) => Err(ProwlError::FailedOver { channel }),
+                None => Ok(()),
+            }
+        }
+        Err(e) => {
+            // Don't let a notification that never went out suppress its retries.
+            if let Some(key) = dedup_key {
+                let mut file = ThrottleState::open()?;
+                file.data.forget(&key);
+                file.save()?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Records the notification in the throttle state and reports whether it should be sent,
+/// first sending summaries for any windows that have closed since the last run.
+async fn check_throttle(
+    client: &ProwlClient,
+    key: &str,
+    window: Duration,
+    summary: Option<SendRequest>,
+    formatter: &dyn OutputFormatter,
+) -> Result<bool> {
+    let now = SystemTime::now();
+    let mut file = ThrottleState::open()?;
+    let summaries = file.data.take_expired(now);
+    let decision = file.data.check(key, window, now, summary);
+    file.save()?;
+
+    delivery::send_summaries(client, &summaries, formatter).await;
+
+    match decision {
+        Decision::Send => Ok(true),
+        Decision::Suppress { repeats, until } => {
+            formatter.format_suppressed(key, repeats, until);
+            Ok(false)
+        }
+    }
+}
+
+/// Submits the notification to `prowl daemon`, which sends it with its own API key.
+#[cfg(unix)]
+async fn send_via_socket(
+    args: &SendArgs,
+    description: String,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let notification = &args.notification;
+    let path = socket::socket_path(args.socket.as_deref(), &Config::load()?);
+    let request = SocketRequest {
+        event: Some(notification.event.clone()),
+        description,
+        priority: Some(PriorityValue::Level(notification.priority(config).as_i8())),
+        url: notification.url.clone(),
+        application: Some(config.application.clone()),
+        to: target::recipient_keys(&args.to)?,
+    };
+
+    let spinner = create_spinner("Sending notification...");
+    let response = socket::submit(&path, &request).await;
+    spinner.finish_and_clear();
+
+    match response? {
+        Sent::Delivered(response) => formatter.format_send_success(&response),
+        Sent::Held(notification) => formatter.format_scheduled(&notification),
+    }
+    Ok(())
+}
+
+/// Returns the notification's message, reading it from stdin when it is "-".
+pub fn read_description(notification: &NotificationArgs) -> Result<String> {
+    if notification.message == "-" {
+        read_stdin()
+    } else {
+        Ok(notification.message.clone())
     }
 }
 
@@ -62,16 +210,3 @@ fn read_stdin() -> Result<String> {
     }
     Ok(lines.join("\n"))
 }
-
-fn create_spinner(message: &str) -> ProgressBar {
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.set_message(message.to_string());
-    spinner.enable_steady_tick(Duration::from_millis(80));
-    spinner
-}
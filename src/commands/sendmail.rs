@@ -0,0 +1,218 @@
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::Path;
+
+use crate::api::{ProwlClient, SendRequest};
+use crate::cli::SendmailArgs;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::mail::{Aliases, Mail};
+use crate::output::OutputFormatter;
+
+/// Sendmail options that take a value, either attached (`-FCronDaemon`) or as the next
+/// argument.
+const VALUE_OPTIONS: &[char] = &[
+    'B', 'b', 'C', 'F', 'f', 'h', 'L', 'N', 'O', 'o', 'p', 'q', 'R', 'r', 'V', 'X',
+];
+
+/// What the sendmail command line asked for.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Options {
+    /// `-t`: also send to the addresses in the message's headers.
+    read_recipients: bool,
+    /// `-i` or `-oi`: a lone "." line doesn't end the message.
+    ignore_dots: bool,
+    /// `-bv`: show the notification instead of sending it.
+    verify: bool,
+    verbose: bool,
+    /// `-F`: the sender's full name, used as the application.
+    full_name: Option<String>,
+    recipients: Vec<String>,
+}
+
+/// The command line to parse: `prowl sendmail -- <args>` when the binary was invoked as
+/// `sendmail`, so none of its options are mistaken for prowl's.
+pub fn argv(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args: Vec<OsString> = args.into_iter().collect();
+    let invoked_as_sendmail = args
+        .first()
+        .and_then(|arg0| Path::new(arg0).file_stem())
+        .is_some_and(|name| name == "sendmail");
+    if invoked_as_sendmail {
+        args.splice(
+            0..1,
+            ["prowl", "sendmail", "--"].into_iter().map(OsString::from),
+        );
+    }
+    args
+}
+
+pub async fn execute(
+    args: &SendmailArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let options = parse_options(&args.args)?;
+
+    let mut raw = Vec::new();
+    std::io::stdin().read_to_end(&mut raw)?;
+    if !options.ignore_dots {
+        raw.truncate(message_end(&raw));
+    }
+    let file_config = Config::load()?;
+    let emergency = file_config.mail.as_ref().and_then(|mail| mail.emergency);
+    let mail = Mail::parse(&raw).with_emergency(emergency.unwrap_or(false));
+
+    let mut recipients = options.recipients.clone();
+    if options.read_recipients {
+        recipients.extend(mail.recipients.iter().cloned());
+    }
+    let apikey =
+        Aliases::load(&file_config)?.resolve(&recipients, config.require_api_key().ok())?;
+
+    let template = SendRequest {
+        apikey,
+        application: options
+            .full_name
+            .clone()
+            .unwrap_or_else(|| config.application.clone()),
+        event: String::new(),
+        description: String::new(),
+        priority: config.priority.unwrap_or_default().as_i8(),
+        url: None,
+        providerkey: config.provider_key.clone(),
+    };
+    let request = mail.request(&template);
+
+    if options.verify {
+        formatter.format_dry_run(&request);
+        return Ok(());
+    }
+
+    let client = ProwlClient::from_config(config, &file_config)?;
+    let sent = delivery::send(&client, &request).await?;
+    // Like sendmail, stay quiet on success unless asked, so cron doesn't mail about it.
+    if options.verbose {
+        match sent {
+            Sent::Delivered(response) => formatter.format_send_success(&response),
+            Sent::Held(notification) => formatter.format_scheduled(&notification),
+        }
+    }
+    Ok(())
+}
+
+fn parse_options(args: &[String]) -> Result<Options> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            options.recipients.extend(args.by_ref().cloned());
+            break;
+        }
+        let Some(flags) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) else {
+            options.recipients.push(arg.clone());
+            continue;
+        };
+
+        for (index, flag) in flags.char_indices() {
+            if VALUE_OPTIONS.contains(&flag) {
+                let attached = &flags[index + flag.len_utf8()..];
+                let value = if attached.is_empty() {
+                    args.next().cloned().ok_or_else(|| {
+                        ProwlError::InvalidInput(format!("sendmail option -{flag} needs a value"))
+                    })?
+                } else {
+                    attached.to_string()
+                };
+                apply_value(&mut options, flag, &value)?;
+                break;
+            }
+            match flag {
+                't' => options.read_recipients = true,
+                'i' => options.ignore_dots = true,
+                'v' => options.verbose = true,
+                // Other flags only tune delivery, which doesn't apply here.
+                _ => {}
+            }
+        }
+    }
+
+    // Recipients may also be given as one comma-separated argument.
+    options.recipients = options
+        .recipients
+        .iter()
+        .flat_map(|recipient| recipient.split(','))
+        .map(|recipient| recipient.trim().to_string())
+        .filter(|recipient| !recipient.is_empty())
+        .collect();
+    Ok(options)
+}
+
+fn apply_value(options: &mut Options, flag: char, value: &str) -> Result<()> {
+    match (flag, value) {
+        ('F', name) => options.full_name = Some(name.to_string()),
+        ('o', "i") => options.ignore_dots = true,
+        ('b', "m") => {}
+        ('b', "v") => options.verify = true,
+        ('b', "s") => {
+            return Err(ProwlError::InvalidInput(
+                "SMTP on stdin (-bs) isn't supported; use `prowl smtp` instead".to_string(),
+            ));
+        }
+        ('b', mode) => {
+            return Err(ProwlError::InvalidInput(format!(
+                "Unsupported sendmail mode -b{mode}"
+            )));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Where the message ends: at a line holding a single ".", or else at the end of input.
+fn message_end(raw: &[u8]) -> usize {
+    let mut offset = 0;
+    for line in raw.split_inclusive(|&b| b == b'\n') {
+        if line.trim_ascii_end() == b"." {
+            return offset;
+        }
+        offset += line.len();
+    }
+    raw.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_cron_command_line() {
+        // cronie's invocation.
+        let options =
+            parse_options(&args(&["-FCronDaemon", "-i", "-B8BITMIME", "-oem", "root"])).unwrap();
+        assert_eq!(
+            options,
+            Options {
+                ignore_dots: true,
+                full_name: Some("CronDaemon".to_string()),
+                recipients: vec!["root".to_string()],
+                ..Options::default()
+            }
+        );
+
+        let options = parse_options(&args(&["-t", "-oi", "-f", "me@host", "-bv"])).unwrap();
+        assert!(options.read_recipients && options.ignore_dots && options.verify);
+        assert!(options.recipients.is_empty());
+        assert!(parse_options(&args(&["-bs"])).is_err());
+
+        assert_eq!(message_end(b"Subject: x\n\nbody\n.\nignored\n"), 17);
+        let argv = argv(["/usr/sbin/sendmail", "-t"].map(OsString::from));
+        assert_eq!(argv, ["prowl", "sendmail", "--", "-t"].map(OsString::from));
+    }
+}
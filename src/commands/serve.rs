@@ -0,0 +1,328 @@
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::future::IntoFuture;
+use std::sync::Arc;
+
+use crate::adapters::{self, PayloadAdapter};
+use crate::api::{PriorityValue, ProwlClient, SendRequest};
+use crate::cli::ServeArgs;
+use crate::commands::shutdown_signal;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, DeliveryQueue};
+use crate::error::{ProwlError, Result};
+use crate::output::{LogLevel, OutputFormatter};
+
+/// Body accepted by `POST /notify`.
+#[derive(Debug, Deserialize)]
+struct WebhookPayload {
+    #[serde(default)]
+    event: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    priority: Option<PriorityValue>,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+#[derive(Clone)]
+struct ServeState {
+    queue: DeliveryQueue,
+    auth_token: Arc<str>,
+    template: Arc<SendRequest>,
+    adapters: Arc<HashMap<String, Box<dyn PayloadAdapter>>>,
+}
+
+pub async fn execute(
+    args: &ServeArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let template = SendRequest {
+        apikey: config.require_api_key()?.to_string(),
+        application: config.application.clone(),
+        event: "Alert".to_string(),
+        description: String::new(),
+        priority: 0,
+        url: None,
+        providerkey: config.provider_key.clone(),
+    };
+
+    let file_config = Config::load()?;
+    let listener = tokio::net::TcpListener::bind(args.listen).await?;
+    let (queue, worker) = delivery::queue(
+        usize::from(args.queue_size),
+        ProwlClient::from_config(config, &file_config)?,
+        args.pace,
+    );
+
+    let adapters = adapters::names(&file_config)
+        .into_iter()
+        .map(|name| {
+            let adapter = adapters::build(&name, &file_config)?;
+            Ok((name, adapter))
+        })
+        .collect::<Result<_>>()?;
+
+    let state = ServeState {
+        queue,
+        auth_token: Arc::from(args.auth_token.as_str()),
+        template: Arc::new(template),
+        adapters: Arc::new(adapters),
+    };
+    let app = router(state);
+
+    formatter.format_log(
+        LogLevel::Info,
+        &format!("Listening on http://{}", listener.local_addr()?),
+    );
+
+    let server = axum::serve(listener, app).with_graceful_shutdown(shutdown_signal());
+    let worker = worker.run(formatter);
+    tokio::pin!(worker);
+
+    // The worker can't finish first: the router holds a queue handle until the server stops.
+    tokio::select! {
+        served = server.into_future() => served?,
+        () = &mut worker => {}
+    }
+
+    // With the router gone the worker drains what's still queued.
+    delivery::drain(worker, formatter).await;
+
+    formatter.format_log(LogLevel::Info, "Shut down");
+    Ok(())
+}
+
+fn router(state: ServeState) -> Router {
+    Router::new()
+        .route("/notify", post(notify))
+        .route("/hooks/{adapter}", post(hook))
+        .route("/health", get(health))
+        .with_state(state)
+}
+
+async fn notify(State(state): State<ServeState>, headers: HeaderMap, body: Bytes) -> Response {
+    if !authorized(&headers, &state.auth_token) {
+        return unauthorized();
+    }
+
+    let request = serde_json::from_slice::<WebhookPayload>(&body)
+        .map_err(|e| ProwlError::InvalidInput(e.to_string()))
+        .and_then(|payload| build_request(payload, &state.template));
+
+    match request {
+        Ok(request) => enqueue(&state.queue, vec![request]),
+        Err(e) => reply(StatusCode::BAD_REQUEST, Err(e.to_string())),
+    }
+}
+
+async fn hook(
+    State(state): State<ServeState>,
+    Path(adapter): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if !authorized(&headers, &state.auth_token) {
+        return unauthorized();
+    }
+
+    let Some(adapter) = state.adapters.get(&adapter) else {
+        return reply(
+            StatusCode::NOT_FOUND,
+            Err(format!("Unknown adapter: {adapter}")),
+        );
+    };
+
+    match adapter.adapt(&body, &state.template) {
+        Ok(requests) => enqueue(&state.queue, requests),
+        Err(e) => reply(StatusCode::BAD_REQUEST, Err(e.to_string())),
+    }
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        axum::Json(json!({ "success": false, "error": "Unauthorized" })),
+    )
+        .into_response()
+}
+
+async fn health() -> Response {
+    reply(StatusCode::OK, Ok(0))
+}
+
+fn enqueue(queue: &DeliveryQueue, requests: Vec<SendRequest>) -> Response {
+    let count = requests.len();
+    if requests
+        .into_iter()
+        .all(|request| queue.try_enqueue(request))
+    {
+        reply(StatusCode::ACCEPTED, Ok(count))
+    } else {
+        reply(
+            StatusCode::SERVICE_UNAVAILABLE,
+            Err("Delivery queue is full".to_string()),
+        )
+    }
+}
+
+fn reply(status: StatusCode, result: std::result::Result<usize, String>) -> Response {
+    let body = match result {
+        Ok(queued) => json!({ "success": true, "queued": queued }),
+        Err(error) => json!({ "success": false, "error": error }),
+    };
+    (status, axum::Json(body)).into_response()
+}
+
+fn build_request(payload: WebhookPayload, template: &SendRequest) -> Result<SendRequest> {
+    let priority = match payload.priority {
+        Some(priority) => priority.as_i8()?,
+        None => template.priority,
+    };
+
+    let request = SendRequest {
+        event: payload.event.unwrap_or_else(|| template.event.clone()),
+        description: payload.description.unwrap_or_default(),
+        priority,
+        url: payload.url,
+        ..template.clone()
+    };
+    request.validate()?;
+    Ok(request)
+}
+
+fn authorized(headers: &HeaderMap, auth_token: &str) -> bool {
+    let Some(presented) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+
+    // Compare in constant time so response timing doesn't leak how much of the token matched.
+    let (presented, expected) = (presented.trim().as_bytes(), auth_token.as_bytes());
+    presented.len() == expected.len()
+        && presented
+            .iter()
+            .zip(expected)
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delivery::DeliveryWorker;
+    use axum::body::Body;
+    use axum::http::Request;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "s3cret";
+
+    /// The router, and the worker for its queue, which has to be kept for the queue to stay
+    /// open but is never run, so whatever is queued stays queued.
+    fn app(queue_size: usize) -> (Router, DeliveryWorker) {
+        let template = SendRequest {
+            apikey: "a".repeat(40),
+            application: "prowl-serve".to_string(),
+            event: "Alert".to_string(),
+            description: String::new(),
+            priority: 0,
+            url: None,
+            providerkey: None,
+        };
+        let (queue, worker) =
+            delivery::queue(queue_size, ProwlClient::new().unwrap(), Duration::ZERO);
+        let adapters = HashMap::from([(
+            "github".to_string(),
+            adapters::build("github", &Config::default()).unwrap(),
+        )]);
+        let app = router(ServeState {
+            queue,
+            auth_token: Arc::from(TOKEN),
+            template: Arc::new(template),
+            adapters: Arc::new(adapters),
+        });
+        (app, worker)
+    }
+
+    async fn post(
+        app: Router,
+        path: &str,
+        token: Option<&str>,
+        body: &str,
+    ) -> (StatusCode, String) {
+        let mut request = Request::post(path);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let response = app
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_bearer_auth() {
+        let (app, _worker) = app(8);
+        let notify = r#"{"event": "Deploy finished"}"#;
+        let (status, _) = post(app.clone(), "/notify", None, notify).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = post(app.clone(), "/notify", Some("s3cre"), notify).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = post(app.clone(), "/hooks/github", Some("wrong!"), "{}").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = post(app, "/notify", Some(TOKEN), notify).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(body, r#"{"queued":1,"success":true}"#);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue() {
+        let (app, _worker) = app(1);
+        let notify = r#"{"event": "Deploy finished"}"#;
+        let (status, _) = post(app.clone(), "/notify", Some(TOKEN), notify).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let (status, body) = post(app, "/notify", Some(TOKEN), notify).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.contains("Delivery queue is full"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_hook_routing() {
+        let payload = r#"{
+            "action": "completed",
+            "repository": {"full_name": "octo/app"},
+            "workflow_run": {"name": "CI", "conclusion": "failure", "head_branch": "main",
+                             "display_title": "Fix parser", "html_url": "https://github.com/octo/app/actions/runs/1"}
+        }"#;
+        let (app, _worker) = app(8);
+        let (status, body) = post(app.clone(), "/hooks/github", Some(TOKEN), payload).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+        assert_eq!(body, r#"{"queued":1,"success":true}"#);
+
+        let (status, body) = post(app.clone(), "/hooks/gitlab", Some(TOKEN), payload).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body.contains("Unknown adapter: gitlab"), "{body}");
+
+        let (status, _) = post(app, "/hooks/github", Some(TOKEN), "not json").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,353 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::task::JoinSet;
+
+use crate::api::{ProwlClient, SendRequest};
+use crate::cli::SmtpArgs;
+use crate::commands::shutdown_signal;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, DeliveryQueue};
+use crate::error::Result;
+use crate::mail::{Aliases, Mail};
+use crate::output::{LogLevel, OutputFormatter};
+
+/// How long a client may take to send a command or a line of data (RFC 5321 §4.5.3.2).
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Largest message accepted, advertised as SIZE.
+const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+/// Longest command or text line read, well above the 1000 octets RFC 5321 allows.
+const MAX_LINE_LEN: u64 = 8 * 1024;
+
+const MAX_RECIPIENTS: usize = 100;
+
+/// What each connection needs to turn mail into notifications.
+struct Mailer {
+    aliases: Aliases,
+    /// Whether `X-Priority: 1` mail is sent as an emergency.
+    emergency: bool,
+    /// API key for recipients without an alias.
+    default_key: Option<String>,
+    template: SendRequest,
+}
+
+impl Mailer {
+    fn request(&self, data: &[u8], recipients: &[String]) -> Result<SendRequest> {
+        let template = SendRequest {
+            apikey: self
+                .aliases
+                .resolve(recipients, self.default_key.as_deref())?,
+            ..self.template.clone()
+        };
+        Ok(Mail::parse(data)
+            .with_emergency(self.emergency)
+            .request(&template))
+    }
+}
+
+pub async fn execute(
+    args: &SmtpArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let file_config = Config::load()?;
+    let mailer = Arc::new(Mailer {
+        aliases: Aliases::load(&file_config)?,
+        emergency: file_config
+            .mail
+            .as_ref()
+            .and_then(|mail| mail.emergency)
+            .unwrap_or(false),
+        default_key: config.require_api_key().ok().map(str::to_string),
+        template: SendRequest {
+            apikey: String::new(),
+            application: config.application.clone(),
+            event: String::new(),
+            description: String::new(),
+            priority: config.priority.unwrap_or_default().as_i8(),
+            url: None,
+            providerkey: config.provider_key.clone(),
+        },
+    });
+
+    let listener = TcpListener::bind(args.listen).await?;
+    let (queue, worker) = delivery::queue(
+        usize::from(args.queue_size),
+        ProwlClient::from_config(config, &file_config)?,
+        args.pace,
+    );
+
+    formatter.format_log(
+        LogLevel::Info,
+        &format!("Listening on smtp://{}", listener.local_addr()?),
+    );
+    let listening = tokio::spawn(accept(listener, mailer, queue));
+
+    let worker = async {
+        if args.dry_run {
+            worker.show(formatter).await;
+        } else {
+            worker.run(formatter).await;
+        }
+    };
+    tokio::pin!(worker);
+
+    tokio::select! {
+        () = shutdown_signal() => {}
+        // The worker can't finish first: the listener holds a queue handle until it stops.
+        () = &mut worker => {}
+    }
+
+    // Stopping the listener ends the sessions in progress, whose mail hasn't been
+    // accepted yet, and lets go of the queue for the worker to drain.
+    listening.abort();
+    let _ = listening.await;
+    delivery::drain(worker, formatter).await;
+
+    formatter.format_log(LogLevel::Info, "Shut down");
+    Ok(())
+}
+
+async fn accept(listener: TcpListener, mailer: Arc<Mailer>, queue: DeliveryQueue) {
+    // Sessions belong to the set, so they end when this task is aborted.
+    let mut sessions = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else {
+                    break;
+                };
+                let mailer = Arc::clone(&mailer);
+                let queue = queue.clone();
+                sessions.spawn(async move {
+                    // A client that hangs up or times out only ends its own session.
+                    let _ = session(stream, &mailer, &queue).await;
+                });
+            }
+            Some(_) = sessions.join_next() => {}
+        }
+    }
+}
+
+/// Speaks enough SMTP for appliances and scripts to hand over mail: HELO/EHLO, MAIL, RCPT,
+/// DATA, RSET, NOOP and QUIT. A message is only accepted once its notification is queued
+/// for delivery.
+async fn session<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    mailer: &Mailer,
+    queue: &DeliveryQueue,
+) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut sender_path: Option<String> = None;
+    let mut recipients: Vec<String> = Vec::new();
+
+    reply(&mut stream, "220 prowl ESMTP ready").await?;
+
+    loop {
+        let Some(line) = read_line(&mut stream).await? else {
+            return Ok(());
+        };
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (verb, arg) = line.split_once(' ').unwrap_or((line, ""));
+
+        let response = match verb.to_ascii_uppercase().as_str() {
+            "HELO" => "250 prowl".to_string(),
+            "EHLO" => format!("250-prowl\r\n250-8BITMIME\r\n250 SIZE {MAX_MESSAGE_LEN}"),
+            "MAIL" => match path(arg, "FROM:") {
+                Some(path) => {
+                    sender_path = Some(path);
+                    recipients.clear();
+                    "250 OK".to_string()
+                }
+                None => "501 Syntax: MAIL FROM:<address>".to_string(),
+            },
+            "RCPT" => match path(arg, "TO:").filter(|path| !path.is_empty()) {
+                _ if sender_path.is_none() => "503 MAIL first".to_string(),
+                _ if recipients.len() >= MAX_RECIPIENTS => "452 Too many recipients".to_string(),
+                Some(address) => {
+                    let resolved = mailer.aliases.resolve(
+                        std::slice::from_ref(&address),
+                        mailer.default_key.as_deref(),
+                    );
+                    match resolved {
+                        Ok(_) => {
+                            recipients.push(address);
+                            "250 OK".to_string()
+                        }
+                        Err(_) => format!("550 No notification target for <{address}>"),
+                    }
+                }
+                None => "501 Syntax: RCPT TO:<address>".to_string(),
+            },
+            "DATA" if recipients.is_empty() => "503 RCPT first".to_string(),
+            "DATA" => {
+                reply(&mut stream, "354 End data with <CR><LF>.<CR><LF>").await?;
+                let Some(data) = read_data(&mut stream).await? else {
+                    return Ok(());
+                };
+                let response = match mailer.request(&data, &recipients) {
+                    Ok(request) => {
+                        if queue.try_enqueue(request) {
+                            "250 OK: queued".to_string()
+                        } else {
+                            "451 Queue full, try again later".to_string()
+                        }
+                    }
+                    Err(e) => format!("554 {e}"),
+                };
+                sender_path = None;
+                recipients.clear();
+                response
+            }
+            "RSET" => {
+                sender_path = None;
+                recipients.clear();
+                "250 OK".to_string()
+            }
+            "NOOP" => "250 OK".to_string(),
+            "VRFY" => "252 Cannot verify, but will accept the message".to_string(),
+            "QUIT" => {
+                reply(&mut stream, "221 Bye").await?;
+                return Ok(());
+            }
+            _ => "502 Command not implemented".to_string(),
+        };
+        reply(&mut stream, &response).await?;
+    }
+}
+
+async fn reply<S: AsyncWrite + Unpin>(stream: &mut S, text: &str) -> std::io::Result<()> {
+    stream.write_all(format!("{text}\r\n").as_bytes()).await?;
+    stream.flush().await
+}
+
+/// Reads one line, or `None` at the end of the connection.
+async fn read_line<S: AsyncRead + Unpin>(
+    stream: &mut BufReader<S>,
+) -> std::io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    let mut limited = (&mut *stream).take(MAX_LINE_LEN);
+    let read = limited.read_until(b'\n', &mut line);
+    match tokio::time::timeout(COMMAND_TIMEOUT, read).await {
+        Ok(Ok(0)) => Ok(None),
+        Ok(Ok(_)) => Ok(Some(line)),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err(std::io::ErrorKind::TimedOut.into()),
+    }
+}
+
+/// Reads the message after DATA up to the line holding a single ".", undoing dot-stuffing.
+/// Text beyond [`MAX_MESSAGE_LEN`] is dropped.
+async fn read_data<S: AsyncRead + Unpin>(
+    stream: &mut BufReader<S>,
+) -> std::io::Result<Option<Vec<u8>>> {
+    let mut data = Vec::new();
+    loop {
+        let Some(line) = read_line(stream).await? else {
+            return Ok(None);
+        };
+        if line.trim_ascii_end() == b"." {
+            return Ok(Some(data));
+        }
+        let line = line.strip_prefix(b".").unwrap_or(&line);
+        if data.len() + line.len() <= MAX_MESSAGE_LEN {
+            data.extend_from_slice(line);
+        }
+    }
+}
+
+/// The address in `FROM:<address> [parameters]` or `TO:<address>`; empty for `<>`.
+fn path(arg: &str, prefix: &str) -> Option<String> {
+    let arg = arg.trim_start();
+    if !arg.get(..prefix.len())?.eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+    let arg = arg[prefix.len()..].trim_start();
+    let address = match arg.strip_prefix('<') {
+        Some(rest) => &rest[..rest.find('>')?],
+        None => arg.split_whitespace().next()?,
+    };
+    Some(address.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::OutputFormat;
+    use crate::notifiers::Notifier;
+    use crate::notifiers::smtp::SmtpNotifier;
+    use crate::notifiers::stand_in::Recorder;
+    use crate::output::get_formatter;
+
+    #[tokio::test]
+    async fn test_accepts_mail_as_notification() {
+        let key = "a".repeat(40);
+        let mailer = Mailer {
+            aliases: Aliases::default(),
+            emergency: false,
+            default_key: Some(key.clone()),
+            template: SendRequest {
+                apikey: String::new(),
+                application: "relay".to_string(),
+                event: String::new(),
+                description: String::new(),
+                priority: 0,
+                url: None,
+                providerkey: None,
+            },
+        };
+        let recorder = Arc::new(Recorder::default());
+        let client = ProwlClient::new()
+            .unwrap()
+            .with_backend(Some(recorder.clone()));
+        let (queue, worker) = delivery::queue(1, client, Duration::ZERO);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+        let listening = tokio::spawn(accept(listener, Arc::new(mailer), queue));
+
+        let notifier = SmtpNotifier::new(
+            Some(server),
+            "ups@nas.local".to_string(),
+            vec!["root".to_string()],
+        );
+        let request = SendRequest {
+            apikey: String::new(),
+            application: "nas".to_string(),
+            event: "On battery".to_string(),
+            description: "Mains lost at 03:12\n.dotted line".to_string(),
+            priority: 0,
+            url: None,
+            providerkey: None,
+        };
+        notifier.notify(&request).await.unwrap();
+        // With the queue full the message is refused for the client to retry, not lost.
+        let refused = notifier.notify(&request).await.unwrap_err().to_string();
+        assert!(refused.contains("451"), "{refused}");
+
+        listening.abort();
+        let _ = listening.await;
+        worker
+            .run(get_formatter(OutputFormat::Quiet).as_ref())
+            .await;
+        let received = recorder.received();
+        assert_eq!(received.len(), 1);
+        let notification = &received[0];
+        assert_eq!(notification.apikey, key);
+        assert_eq!(notification.application, "relay");
+        assert_eq!(notification.event, "[nas] On battery");
+        assert_eq!(
+            notification.description,
+            "Mains lost at 03:12\n.dotted line"
+        );
+        assert_eq!(notification.priority, 0);
+
+        assert_eq!(path(" FROM:<a@b> SIZE=10", "FROM:").as_deref(), Some("a@b"));
+        assert_eq!(path("FROM:<>", "FROM:").as_deref(), Some(""));
+        assert_eq!(path("to:root", "TO:").as_deref(), Some("root"));
+        assert_eq!(path("<root>", "TO:"), None);
+    }
+}
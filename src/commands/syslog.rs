@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::adapters::truncate;
+use crate::api::{Priority, ProwlClient, SendRequest};
+use crate::cli::{ListenAddr, SyslogArgs};
+use crate::commands::shutdown_signal;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery;
+use crate::error::Result;
+use crate::output::{LogLevel, OutputFormatter};
+use crate::syslog::{self, SyslogMessage};
+
+/// Largest message accepted over either transport.
+const MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+/// Longest octet-counting length prefix read, digits and the space after them.
+const MAX_LENGTH_PREFIX: u64 = 12;
+
+/// Lines kept per coalesced notification; later messages are only counted.
+const MAX_BURST_LINES: usize = 20;
+
+/// A raw message and the address it came from, passed from the listener tasks.
+type Received = (String, SocketAddr);
+
+pub async fn execute(
+    args: &SyslogArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let template = SendRequest {
+        apikey: config.require_api_key()?.to_string(),
+        application: config.application.clone(),
+        event: "Syslog".to_string(),
+        description: String::new(),
+        priority: 0,
+        url: None,
+        providerkey: config.provider_key.clone(),
+    };
+
+    let (sender, mut received) = mpsc::channel::<Received>(1024);
+    let local_addr = match args.listen {
+        ListenAddr::Udp(addr) => {
+            let socket = UdpSocket::bind(addr).await?;
+            let local_addr = format!("udp://{}", socket.local_addr()?);
+            tokio::spawn(receive_udp(socket, sender));
+            local_addr
+        }
+        ListenAddr::Tcp(addr) => {
+            let listener = TcpListener::bind(addr).await?;
+            let local_addr = format!("tcp://{}", listener.local_addr()?);
+            tokio::spawn(accept_tcp(listener, sender));
+            local_addr
+        }
+    };
+
+    let (queue, worker) = delivery::queue(
+        usize::from(args.queue_size),
+        ProwlClient::from_config(config, &Config::load()?)?,
+        args.pace,
+    );
+    let mut bursts = Bursts::new(args.coalesce, template);
+
+    formatter.format_log(LogLevel::Info, &format!("Listening on {local_addr}"));
+
+    let worker = worker.run(formatter);
+    tokio::pin!(worker);
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut tick = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        tokio::select! {
+            Some((line, peer)) = received.recv() => {
+                let Some(message) = syslog::parse(&line) else {
+                    formatter.format_log(
+                        LogLevel::Warning,
+                        &format!("Ignored malformed message from {peer}"),
+                    );
+                    continue;
+                };
+                if matches(args, &message) {
+                    bursts.push(message, peer, Instant::now());
+                }
+            }
+            _ = tick.tick() => {}
+            _ = &mut shutdown => break,
+            // The worker can't finish first: `queue` is held until the loop ends.
+            () = &mut worker => break,
+        }
+
+        for request in bursts.take_ready(Instant::now()) {
+            delivery::deliver(request, &queue, args.dry_run, formatter);
+        }
+    }
+
+    for request in bursts.take_all() {
+        delivery::deliver(request, &queue, args.dry_run, formatter);
+    }
+    drop(queue);
+
+    delivery::drain(worker, formatter).await;
+
+    formatter.format_log(LogLevel::Info, "Shut down");
+    Ok(())
+}
+
+fn matches(args: &SyslogArgs, message: &SyslogMessage) -> bool {
+    message.at_least(args.severity)
+        && (args.facility.is_empty() || args.facility.contains(&message.facility))
+        && (args.program.is_empty()
+            || message
+                .program
+                .as_ref()
+                .is_some_and(|program| args.program.contains(program)))
+        && args
+            .pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&message.message))
+}
+
+/// Messages from one host and program collected into a single notification.
+struct Burst {
+    started: Instant,
+    lines: Vec<String>,
+    count: usize,
+    priority: Priority,
+}
+
+/// Groups matching messages by host and program for the coalescing window.
+struct Bursts {
+    window: Duration,
+    template: SendRequest,
+    pending: HashMap<(String, String), Burst>,
+}
+
+impl Bursts {
+    fn new(window: Duration, template: SendRequest) -> Self {
+        Bursts {
+            window,
+            template,
+            pending: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, message: SyslogMessage, peer: SocketAddr, now: Instant) {
+        let priority = message.priority();
+        let host = message.hostname.unwrap_or_else(|| peer.ip().to_string());
+        let program = message.program.unwrap_or_else(|| "syslog".to_string());
+
+        let burst = self
+            .pending
+            .entry((host, program))
+            .or_insert_with(|| Burst {
+                started: now,
+                lines: Vec::new(),
+                count: 0,
+                priority,
+            });
+        burst.count += 1;
+        burst.priority = burst.priority.max(priority);
+        if burst.lines.len() < MAX_BURST_LINES {
+            burst.lines.push(message.message);
+        }
+    }
+
+    /// Removes the bursts whose window has closed and turns them into requests.
+    fn take_ready(&mut self, now: Instant) -> Vec<SendRequest> {
+        let ready: Vec<(String, String)> = self
+            .pending
+            .iter()
+            .filter(|(_, burst)| now.duration_since(burst.started) >= self.window)
+            .map(|(key, _)| key.clone())
+            .collect();
+        ready
+            .into_iter()
+            .filter_map(|key| {
+                let burst = self.pending.remove(&key)?;
+                Some(self.build_request(key, burst))
+            })
+            .collect()
+    }
+
+    fn take_all(&mut self) -> Vec<SendRequest> {
+        let pending = std::mem::take(&mut self.pending);
+        pending
+            .into_iter()
+            .map(|(key, burst)| self.build_request(key, burst))
+            .collect()
+    }
+
+    fn build_request(&self, (host, program): (String, String), burst: Burst) -> SendRequest {
+        let mut event = if burst.count > 1 {
+            format!("{host}: {program} ({} messages)", burst.count)
+        } else {
+            format!("{host}: {program}")
+        };
+        truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+
+        let mut description = burst.lines.join("\n");
+        if burst.count > burst.lines.len() {
+            description.push_str(&format!("\n… and {} more", burst.count - burst.lines.len()));
+        }
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        SendRequest {
+            event,
+            description,
+            priority: burst.priority.as_i8(),
+            ..self.template.clone()
+        }
+    }
+}
+
+async fn receive_udp(socket: UdpSocket, sender: mpsc::Sender<Received>) {
+    let mut buffer = vec![0; MAX_MESSAGE_LEN];
+    while let Ok((len, peer)) = socket.recv_from(&mut buffer).await {
+        let line = String::from_utf8_lossy(&buffer[..len]).into_owned();
+        if sender.send((line, peer)).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn accept_tcp(listener: TcpListener, sender: mpsc::Sender<Received>) {
+    while let Ok((stream, peer)) = listener.accept().await {
+        tokio::spawn(receive_tcp(stream, peer, sender.clone()));
+    }
+}
+
+/// Reads messages framed either by octet counting (`<len> <msg>`, RFC 6587) or by newlines.
+async fn receive_tcp(stream: TcpStream, peer: SocketAddr, sender: mpsc::Sender<Received>) {
+    let mut reader = BufReader::new(stream);
+    let mut frame = Vec::new();
+
+    loop {
+        frame.clear();
+        let Ok(buffered) = reader.fill_buf().await else {
+            return;
+        };
+        let Some(&first) = buffered.first() else {
+            return;
+        };
+
+        if first.is_ascii_digit() {
+            let mut length = Vec::new();
+            if (&mut reader)
+                .take(MAX_LENGTH_PREFIX)
+                .read_until(b' ', &mut length)
+                .await
+                .is_err()
+            {
+                return;
+            }
+            let Some(length) = std::str::from_utf8(&length)
+                .ok()
+                .and_then(|length| length.trim().parse::<usize>().ok())
+                .filter(|length| *length <= MAX_MESSAGE_LEN)
+            else {
+                return;
+            };
+            frame.resize(length, 0);
+            if reader.read_exact(&mut frame).await.is_err() {
+                return;
+            }
+        } else {
+            match (&mut reader)
+                .take(MAX_MESSAGE_LEN as u64)
+                .read_until(b'\n', &mut frame)
+                .await
+            {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {}
+            }
+        }
+
+        let line = String::from_utf8_lossy(&frame).into_owned();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if sender.send((line, peer)).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Command {
+        #[command(flatten)]
+        args: SyslogArgs,
+    }
+
+    fn args(flags: &[&str]) -> SyslogArgs {
+        Command::try_parse_from(std::iter::once("syslog").chain(flags.iter().copied()))
+            .unwrap()
+            .args
+    }
+
+    fn template() -> SendRequest {
+        SendRequest {
+            application: "syslog".to_string(),
+            event: "Syslog".to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let failed =
+            syslog::parse("<35>Oct 11 22:14:15 host1 sshd[12]: Failed password for root").unwrap();
+        let info = syslog::parse("<38>Oct 11 22:14:15 host1 sshd[12]: Accepted publickey for ops")
+            .unwrap();
+        let cron = syslog::parse("<75>Oct 11 22:14:15 host1 CRON[7]: Failed to run job").unwrap();
+
+        let defaults = args(&[]);
+        assert!(matches(&defaults, &failed));
+        assert!(!matches(&defaults, &info));
+        assert!(matches(&args(&["--severity", "info"]), &info));
+
+        let sshd = args(&["--program", "sshd,su", "--match", "^Failed"]);
+        assert!(matches(&sshd, &failed));
+        assert!(!matches(&sshd, &cron));
+        assert!(!matches(&args(&["--match", "password"]), &cron));
+
+        let auth = args(&["--facility", "auth"]);
+        assert!(matches(&auth, &failed));
+        assert!(!matches(&auth, &cron));
+    }
+
+    #[test]
+    fn test_bursts_coalesce_by_host_and_program() {
+        let peer: SocketAddr = "192.0.2.7:514".parse().unwrap();
+        let start = Instant::now();
+        let window = Duration::from_secs(10);
+        let mut bursts = Bursts::new(window, template());
+
+        for i in 0..MAX_BURST_LINES + 2 {
+            let line = format!("<36>Oct 11 22:14:15 host1 sshd[12]: attempt {i}");
+            bursts.push(syslog::parse(&line).unwrap(), peer, start);
+        }
+        bursts.push(
+            syslog::parse("<34>Oct 11 22:14:15 host1 sshd[12]: gave up").unwrap(),
+            peer,
+            start,
+        );
+        bursts.push(
+            syslog::parse("<11>disk: sda failing").unwrap(),
+            peer,
+            start + Duration::from_secs(5),
+        );
+
+        assert!(bursts.take_ready(start + Duration::from_secs(9)).is_empty());
+
+        let ready = bursts.take_ready(start + window);
+        assert_eq!(ready.len(), 1);
+        let sshd = &ready[0];
+        assert_eq!(
+            sshd.event,
+            format!("host1: sshd ({} messages)", MAX_BURST_LINES + 3)
+        );
+        // The most severe message sets the priority.
+        assert_eq!(sshd.priority, Priority::High.as_i8());
+        assert!(sshd.description.starts_with("attempt 0\nattempt 1\n"));
+        assert!(
+            sshd.description.ends_with("\n… and 3 more"),
+            "{}",
+            sshd.description
+        );
+
+        // Without a hostname the peer address stands in; what's left goes out at shutdown.
+        let rest = bursts.take_all();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].event, "192.0.2.7: disk");
+        assert_eq!(rest[0].description, "sda failing");
+        assert!(bursts.take_all().is_empty());
+    }
+}
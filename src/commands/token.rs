@@ -1,12 +1,14 @@
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
-
 use crate::api::{ProwlClient, TokenRequest};
+use crate::cli::TokenArgs;
 use crate::config::ResolvedConfig;
 use crate::error::Result;
-use crate::output::OutputFormatter;
+use crate::output::{OutputFormatter, create_spinner};
 
-pub async fn execute(config: &ResolvedConfig, formatter: &dyn OutputFormatter) -> Result<()> {
+pub async fn execute(
+    args: &TokenArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
     let provider_key = config.require_provider_key()?;
 
     let request = TokenRequest {
@@ -22,22 +24,9 @@ pub async fn execute(config: &ResolvedConfig, formatter: &dyn OutputFormatter) -
 
     match response {
         Ok(resp) => {
-            formatter.format_token_success(&resp);
+            formatter.format_token_success(&resp, args.qr.mode);
             Ok(())
         }
         Err(e) => Err(e),
     }
 }
-
-fn create_spinner(message: &str) -> ProgressBar {
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.set_message(message.to_string());
-    spinner.enable_steady_tick(Duration::from_millis(80));
-    spinner
-}
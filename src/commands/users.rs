@@ -0,0 +1,167 @@
+use crate::api::{ProwlClient, SendRequest};
+use crate::cli::{UsersCommand, UsersSendArgs};
+use crate::commands::send::read_description;
+use crate::commands::verify::verify_keys;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::output::{KeyOutcome, KeyStatus, OutputFormatter, create_spinner, outcomes_result};
+use crate::registry::KeyRegistry;
+
+const VERIFY_CONCURRENCY: usize = 4;
+
+pub async fn execute(
+    cmd: &UsersCommand,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    match cmd {
+        UsersCommand::List => list_users(formatter),
+        UsersCommand::Add { label, api_key } => add_user(label, api_key, formatter),
+        UsersCommand::Remove { label } => remove_user(label, formatter),
+        UsersCommand::Verify { users } => verify_users(users, config, formatter).await,
+        UsersCommand::Prune { dry_run } => prune_users(*dry_run, config, formatter).await,
+        UsersCommand::Send(args) => send_to_users(args, config, formatter).await,
+    }
+}
+
+fn list_users(formatter: &dyn OutputFormatter) -> Result<()> {
+    let registry = KeyRegistry::load()?;
+    formatter.format_users_list(&registry, &KeyRegistry::path()?);
+    Ok(())
+}
+
+fn add_user(label: &str, api_key: &str, formatter: &dyn OutputFormatter) -> Result<()> {
+    let mut registry = KeyRegistry::open()?;
+    registry.data.insert(label, api_key);
+    registry.save()?;
+    formatter.format_users_update("add", label);
+    Ok(())
+}
+
+fn remove_user(label: &str, formatter: &dyn OutputFormatter) -> Result<()> {
+    let mut registry = KeyRegistry::open()?;
+    if registry.data.users.remove(label).is_none() {
+        return Err(ProwlError::Config(format!("Unknown user: {label}")));
+    }
+    registry.save()?;
+    formatter.format_users_update("remove", label);
+    Ok(())
+}
+
+async fn verify_users(
+    labels: &[String],
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let provider_key = config.require_provider_key()?;
+    let registry = KeyRegistry::load()?;
+    let users = registry.select(labels)?;
+
+    let outcomes = verify_keys(owned_keys(&users), Some(provider_key), VERIFY_CONCURRENCY).await?;
+
+    formatter.format_key_outcomes("verify", &outcomes);
+    outcomes_result(&outcomes)
+}
+
+async fn prune_users(
+    dry_run: bool,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let provider_key = config.require_provider_key()?;
+    let registry = KeyRegistry::load()?;
+    let users = registry.select(&[])?;
+
+    let outcomes = verify_keys(owned_keys(&users), Some(provider_key), VERIFY_CONCURRENCY).await?;
+
+    // Only keys the API explicitly rejected are removed; rate limiting or a network failure
+    // says nothing about whether the key itself is still good.
+    let invalid: Vec<KeyOutcome> = outcomes
+        .into_iter()
+        .filter(|o| o.status == KeyStatus::Invalid)
+        .collect();
+
+    if !dry_run {
+        // Verifying can take a while, so the registry is only locked for the removal itself;
+        // an entry re-registered with a new key in the meantime is kept.
+        let mut registry = KeyRegistry::open()?;
+        for outcome in &invalid {
+            let unchanged = registry
+                .data
+                .users
+                .get(&outcome.label)
+                .is_some_and(|user| user.api_key == outcome.api_key);
+            if unchanged {
+                registry.data.users.remove(&outcome.label);
+            }
+        }
+        registry.save()?;
+    }
+
+    let action = if dry_run { "prune (dry run)" } else { "prune" };
+    formatter.format_key_outcomes(action, &invalid);
+    Ok(())
+}
+
+async fn send_to_users(
+    args: &UsersSendArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let provider_key = config.require_provider_key()?;
+    let registry = KeyRegistry::load()?;
+    let users = registry.select(&args.users)?;
+
+    let notification = &args.notification;
+    let template = SendRequest {
+        apikey: String::new(),
+        application: config.application.clone(),
+        event: notification.event.clone(),
+        description: read_description(notification)?,
+        priority: notification.priority(config).as_i8(),
+        url: notification.url.clone(),
+        providerkey: Some(provider_key.to_string()),
+    };
+    template.validate()?;
+
+    if args.dry_run {
+        let keys: Vec<&str> = users.iter().map(|(_, key)| *key).collect();
+        formatter.format_dry_run(&SendRequest {
+            apikey: keys.join(","),
+            ..template
+        });
+        return Ok(());
+    }
+
+    // Registered users are Prowl keys, whatever backend the profile sends through.
+    let client = ProwlClient::from_config(config, &Config::load()?)?.with_backend(None);
+    let spinner = create_spinner("Sending notifications...");
+    let mut outcomes = Vec::with_capacity(users.len());
+
+    // One request per user so a single revoked key can't fail delivery to everyone else.
+    for (label, api_key) in users {
+        spinner.set_message(format!("Sending to {label}..."));
+        let request = SendRequest {
+            apikey: api_key.to_string(),
+            ..template.clone()
+        };
+        outcomes.push(match delivery::send(&client, &request).await {
+            Ok(Sent::Held(_)) => KeyOutcome::held(label, api_key),
+            Ok(Sent::Delivered(response)) => KeyOutcome::new(label, api_key, &Ok(response)),
+            Err(e) => KeyOutcome::new(label, api_key, &Err(e)),
+        });
+    }
+
+    spinner.finish_and_clear();
+
+    formatter.format_key_outcomes("send", &outcomes);
+    outcomes_result(&outcomes)
+}
+
+fn owned_keys(users: &[(&str, &str)]) -> Vec<(String, String)> {
+    users
+        .iter()
+        .map(|(label, api_key)| (label.to_string(), api_key.to_string()))
+        .collect()
+}
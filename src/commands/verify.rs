@@ -1,12 +1,23 @@
-use indicatif::{ProgressBar, ProgressStyle};
-use std::time::Duration;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
 use crate::api::{ProwlClient, VerifyRequest};
-use crate::config::ResolvedConfig;
-use crate::error::Result;
-use crate::output::OutputFormatter;
+use crate::cli::VerifyArgs;
+use crate::config::{Config, ResolvedConfig};
+use crate::error::{ProwlError, Result};
+use crate::output::{KeyOutcome, OutputFormatter, create_spinner, outcomes_result};
+
+pub async fn execute(
+    args: &VerifyArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    if args.all || args.keys_file.is_some() {
+        return verify_many(args, config, formatter).await;
+    }
 
-pub async fn execute(config: &ResolvedConfig, formatter: &dyn OutputFormatter) -> Result<()> {
     let api_key = config.require_api_key()?;
 
     let request = VerifyRequest {
@@ -30,15 +41,141 @@ pub async fn execute(config: &ResolvedConfig, formatter: &dyn OutputFormatter) -
     }
 }
 
-fn create_spinner(message: &str) -> ProgressBar {
-    let spinner = ProgressBar::new_spinner();
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-            .template("{spinner:.cyan} {msg}")
-            .unwrap(),
-    );
-    spinner.set_message(message.to_string());
-    spinner.enable_steady_tick(Duration::from_millis(80));
-    spinner
+async fn verify_many(
+    args: &VerifyArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let mut keys = Vec::new();
+
+    if args.all {
+        let file_config = Config::load()?;
+        if let Some(api_key) = file_config.api_key {
+            keys.push(("default".to_string(), api_key));
+        }
+        for (name, profile) in file_config.profiles {
+            if let Some(api_key) = profile.api_key {
+                keys.push((name, api_key));
+            }
+        }
+    }
+
+    if let Some(ref path) = args.keys_file {
+        keys.extend(read_keys_file(path)?);
+    }
+
+    // The same key is often shared between profiles; verifying it twice only costs quota.
+    let mut seen = std::collections::HashSet::new();
+    keys.retain(|(_, api_key)| seen.insert(api_key.clone()));
+
+    let outcomes = verify_keys(
+        keys,
+        config.provider_key.as_deref(),
+        usize::from(args.concurrency),
+    )
+    .await?;
+
+    formatter.format_key_outcomes("verify", &outcomes);
+    outcomes_result(&outcomes)
+}
+
+/// Verifies `keys` (label, API key pairs) with at most `concurrency` requests in flight,
+/// returning one outcome per key in the original order.
+pub async fn verify_keys(
+    keys: Vec<(String, String)>,
+    provider_key: Option<&str>,
+    concurrency: usize,
+) -> Result<Vec<KeyOutcome>> {
+    let client = ProwlClient::new()?;
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let total = keys.len();
+
+    let spinner = create_spinner(&format!("Verifying {total} API keys..."));
+
+    let mut tasks = JoinSet::new();
+    for (index, (label, api_key)) in keys.into_iter().enumerate() {
+        let client = client.clone();
+        let semaphore = Arc::clone(&semaphore);
+        let request = VerifyRequest {
+            apikey: api_key,
+            providerkey: provider_key.map(String::from),
+        };
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = client.verify(&request).await;
+            (index, KeyOutcome::new(&label, &request.apikey, &result))
+        });
+    }
+
+    let mut outcomes: Vec<Option<KeyOutcome>> = vec![None; total];
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let (index, outcome) = joined.map_err(std::io::Error::from)?;
+        outcomes[index] = Some(outcome);
+        done += 1;
+        spinner.set_message(format!("Verifying API keys ({done}/{total})..."));
+    }
+
+    spinner.finish_and_clear();
+    Ok(outcomes.into_iter().flatten().collect())
+}
+
+fn read_keys_file(path: &Path) -> Result<Vec<(String, String)>> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut keys = Vec::new();
+
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let entry = match (fields.next(), fields.next(), fields.next()) {
+            (Some(key), None, None) => (format!("line {}", number + 1), key.to_string()),
+            (Some(label), Some(key), None) => (label.to_string(), key.to_string()),
+            _ => {
+                return Err(ProwlError::Config(format!(
+                    "{}:{}: expected \"<key>\" or \"<label> <key>\"",
+                    path.display(),
+                    number + 1
+                )));
+            }
+        };
+        keys.push(entry);
+    }
+
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_keys_file() {
+        let dir = std::env::temp_dir().join(format!("prowl-verify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (alice, bob) = ("a".repeat(40), "b".repeat(40));
+
+        let path = dir.join("keys.txt");
+        std::fs::write(
+            &path,
+            format!("# team keys\n\n  alice {alice}\n{bob}\n   # old\n"),
+        )
+        .unwrap();
+        assert_eq!(
+            read_keys_file(&path).unwrap(),
+            [
+                ("alice".to_string(), alice.clone()),
+                ("line 4".to_string(), bob.clone())
+            ]
+        );
+
+        std::fs::write(&path, format!("alice {alice}\nbob {bob} extra\n")).unwrap();
+        let error = read_keys_file(&path).unwrap_err().to_string();
+        assert!(error.contains("keys.txt:2: expected"), "{error}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
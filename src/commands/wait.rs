@@ -0,0 +1,315 @@
+use chrono::Local;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::Instant;
+
+use crate::adapters::truncate;
+use crate::api::{Priority, ProwlClient, SendRequest};
+use crate::cli::WaitArgs;
+use crate::config::{Config, ResolvedConfig};
+use crate::delivery::{self, Sent};
+use crate::error::{ProwlError, Result};
+use crate::notifiers::command::shell;
+use crate::output::{OutputFormatter, create_spinner};
+
+/// Longest a single attempt to connect for `--port-open` may take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What `prowl wait` is waiting for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    Exited { pid: u32, name: Option<String> },
+    FileExists(PathBuf),
+    PortOpen(String),
+    Succeeds(String),
+}
+
+impl Condition {
+    fn from_args(args: &WaitArgs) -> Result<Condition> {
+        if let Some(pid) = args.pid {
+            if !process_running(pid) {
+                return Err(ProwlError::InvalidInput(format!(
+                    "No process with ID {pid}"
+                )));
+            }
+            return Ok(Condition::Exited {
+                pid,
+                name: process_name(pid),
+            });
+        }
+        if let Some(path) = &args.file_exists {
+            return Ok(Condition::FileExists(path.clone()));
+        }
+        if let Some(address) = &args.port_open {
+            let valid = address
+                .rsplit_once(':')
+                .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+            if !valid {
+                return Err(ProwlError::InvalidInput(format!(
+                    "--port-open takes HOST:PORT, not \"{address}\""
+                )));
+            }
+            return Ok(Condition::PortOpen(address.clone()));
+        }
+        match &args.until {
+            Some(command) => Ok(Condition::Succeeds(command.clone())),
+            None => Err(ProwlError::InvalidInput(
+                "Nothing to wait for; give --pid, --file-exists, --port-open or --until"
+                    .to_string(),
+            )),
+        }
+    }
+
+    /// Whether the condition holds now. `limit` bounds how long finding out may take.
+    async fn holds(&self, limit: Duration) -> bool {
+        match self {
+            Condition::Exited { pid, .. } => !process_running(*pid),
+            Condition::FileExists(path) => path.exists(),
+            Condition::PortOpen(address) => {
+                let connect = TcpStream::connect(address.as_str());
+                matches!(
+                    tokio::time::timeout(limit.min(CONNECT_TIMEOUT), connect).await,
+                    Ok(Ok(_))
+                )
+            }
+            Condition::Succeeds(command) => {
+                let status = shell(command)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true)
+                    .status();
+                matches!(
+                    tokio::time::timeout(limit, status).await,
+                    Ok(Ok(status)) if status.success()
+                )
+            }
+        }
+    }
+
+    /// "process 1234 (rsync) to exit", as in "Waiting for …".
+    fn awaited(&self) -> String {
+        match self {
+            Condition::Exited { pid, name } => format!("process {} to exit", process(*pid, name)),
+            Condition::FileExists(path) => format!("{} to exist", path.display()),
+            Condition::PortOpen(address) => format!("{address} to accept connections"),
+            Condition::Succeeds(command) => format!("`{command}` to succeed"),
+        }
+    }
+
+    /// "Process 1234 (rsync) exited"
+    fn happened(&self) -> String {
+        match self {
+            Condition::Exited { pid, name } => format!("Process {} exited", process(*pid, name)),
+            Condition::FileExists(path) => format!("{} exists", path.display()),
+            Condition::PortOpen(address) => format!("{address} is accepting connections"),
+            Condition::Succeeds(command) => format!("`{command}` succeeded"),
+        }
+    }
+}
+
+/// "1234 (rsync)"
+fn process(pid: u32, name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!("{pid} ({name})"),
+        None => pid.to_string(),
+    }
+}
+
+pub async fn execute(
+    args: &WaitArgs,
+    config: &ResolvedConfig,
+    formatter: &dyn OutputFormatter,
+) -> Result<()> {
+    let condition = Condition::from_args(args)?;
+    // Fail on a missing key now rather than after the wait.
+    let apikey = config.require_api_key()?.to_string();
+    let started = Local::now();
+
+    let spinner = create_spinner(&format!("Waiting for {}...", condition.awaited()));
+    let waited = wait(&condition, args.interval, args.timeout).await;
+    spinner.finish_and_clear();
+
+    let since = started.format("%H:%M:%S");
+    let (mut event, description, priority) = match waited {
+        Some(elapsed) => (
+            args.event.clone().unwrap_or_else(|| condition.happened()),
+            format!("Waited {} (since {since}).", format_elapsed(elapsed)),
+            args.priority.or(config.priority).unwrap_or_default(),
+        ),
+        None => (
+            format!("Timed out waiting for {}", condition.awaited()),
+            format!(
+                "Gave up after {} (waiting since {since}).",
+                format_elapsed(args.timeout.unwrap_or_default())
+            ),
+            Priority::High,
+        ),
+    };
+    truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+
+    let request = SendRequest {
+        apikey,
+        application: config.application.clone(),
+        event,
+        description,
+        priority: priority.as_i8(),
+        url: None,
+        providerkey: config.provider_key.clone(),
+    };
+
+    if args.dry_run {
+        formatter.format_dry_run(&request);
+    } else {
+        let client = ProwlClient::from_config(config, &Config::load()?)?;
+
+        let spinner = create_spinner("Sending notification...");
+        let response = delivery::send(&client, &request).await;
+        spinner.finish_and_clear();
+        match response? {
+            Sent::Delivered(response) => formatter.format_send_success(&response),
+            Sent::Held(notification) => formatter.format_scheduled(&notification),
+        }
+    }
+
+    // Exit non-zero on a timeout so scripts can tell, whether or not it was notified.
+    match waited {
+        Some(_) => Ok(()),
+        None => Err(ProwlError::WaitTimedOut {
+            condition: condition.awaited(),
+        }),
+    }
+}
+
+/// Checks `condition` every `interval` until it holds, returning how long that took, or
+/// `None` once `timeout` has passed.
+async fn wait(
+    condition: &Condition,
+    interval: Duration,
+    timeout: Option<Duration>,
+) -> Option<Duration> {
+    let started = Instant::now();
+    loop {
+        let remaining = timeout.map(|timeout| timeout.saturating_sub(started.elapsed()));
+        if condition.holds(remaining.unwrap_or(Duration::MAX)).await {
+            return Some(started.elapsed());
+        }
+        let remaining = timeout.map(|timeout| timeout.saturating_sub(started.elapsed()));
+        if remaining.is_some_and(|remaining| remaining.is_zero()) {
+            return None;
+        }
+        tokio::time::sleep(remaining.map_or(interval, |remaining| interval.min(remaining))).await;
+    }
+}
+
+/// Rounded to whole seconds; "1h 2m 5s".
+fn format_elapsed(elapsed: Duration) -> String {
+    humantime::format_duration(Duration::from_secs(elapsed.as_secs().max(1))).to_string()
+}
+
+#[cfg(target_os = "linux")]
+fn process_running(pid: u32) -> bool {
+    // A zombie has exited; it's only waiting for its parent to collect the status.
+    std::fs::read_to_string(format!("/proc/{pid}/stat")).is_ok_and(|stat| {
+        stat.rsplit_once(')')
+            .is_none_or(|(_, rest)| !rest.trim_start().starts_with('Z'))
+    })
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_running(pid: u32) -> bool {
+    std::process::Command::new("ps")
+        .args(["-p", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(windows)]
+fn process_running(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH", "/FO", "CSV"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{pid}\"")))
+}
+
+#[cfg(target_os = "linux")]
+fn process_name(pid: u32) -> Option<String> {
+    let name = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+    Some(name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_name(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "comm=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let name = String::from_utf8_lossy(&output.stdout);
+    let name = std::path::Path::new(name.trim())
+        .file_name()?
+        .to_string_lossy()
+        .into_owned();
+    Some(name).filter(|name| !name.is_empty())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_conditions() {
+        let dir = std::env::temp_dir().join(format!("prowl-wait-{}", std::process::id()));
+        let file = Condition::FileExists(dir.clone());
+        assert!(!file.holds(Duration::from_secs(1)).await);
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(file.holds(Duration::from_secs(1)).await);
+        std::fs::remove_dir(&dir).unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let port = Condition::PortOpen(address);
+        assert!(port.holds(Duration::from_secs(1)).await);
+        drop(listener);
+        assert!(!port.holds(Duration::from_secs(1)).await);
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let exited = Condition::Exited {
+            pid: child.id(),
+            name: Some("sleep".to_string()),
+        };
+        assert_eq!(
+            exited.happened(),
+            format!("Process {} (sleep) exited", child.id())
+        );
+        assert!(process_name(std::process::id()).is_some());
+        assert!(!exited.holds(Duration::from_secs(1)).await);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(exited.holds(Duration::from_secs(1)).await);
+
+        let command = Condition::Succeeds("test -e /".to_string());
+        assert_eq!(
+            wait(&command, Duration::from_millis(10), None)
+                .await
+                .map(|_| ()),
+            Some(())
+        );
+        let command = Condition::Succeeds("false".to_string());
+        assert_eq!(
+            wait(
+                &command,
+                Duration::from_millis(10),
+                Some(Duration::from_millis(50))
+            )
+            .await,
+            None
+        );
+    }
+}
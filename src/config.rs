@@ -1,8 +1,13 @@
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
+use crate::api::Priority;
 use crate::error::{ProwlError, Result};
+use crate::notifiers::{self, Notifier};
+use crate::target::Target;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
@@ -12,6 +17,303 @@ pub struct Config {
     pub provider_key: Option<String>,
     #[serde(default)]
     pub application: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alertmanager: Option<AlertmanagerConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub adapters: BTreeMap<String, JsonAdapterConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daemon: Option<DaemonConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<ScheduleConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub heartbeats: BTreeMap<String, HeartbeatConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checks: BTreeMap<String, CheckConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover: Vec<FailoverConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channels: BTreeMap<String, BackendConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail: Option<MailConfig>,
+}
+
+/// A named set of credentials, selected with `--profile` or `PROWL_PROFILE`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub provider_key: Option<String>,
+    #[serde(default)]
+    pub application: Option<String>,
+    /// Push service to send through instead of Prowl.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<BackendConfig>,
+}
+
+/// `[profiles.<name>.backend]` or `[channels.<name>]`: a way of delivering notifications
+/// other than Prowl. Notifications keep their application, title, description, priority and
+/// url; the API key isn't used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum BackendConfig {
+    Ntfy {
+        /// Server base URL [default: https://ntfy.sh].
+        #[serde(default)]
+        server: Option<String>,
+        topic: String,
+        /// Access token, for protected topics.
+        #[serde(default)]
+        token: Option<String>,
+    },
+    Pushover {
+        /// Application token.
+        token: String,
+        /// User or group key.
+        user: String,
+        /// Send to this device only.
+        #[serde(default)]
+        device: Option<String>,
+        /// API base URL [default: https://api.pushover.net].
+        #[serde(default)]
+        server: Option<String>,
+    },
+    Gotify {
+        /// Server base URL.
+        server: String,
+        /// Application token.
+        token: String,
+    },
+    /// POSTs each notification as a JSON object.
+    Webhook {
+        url: String,
+        /// Extra request headers, e.g. for authentication.
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+    /// Emails each notification through an SMTP relay that accepts mail without
+    /// authentication, such as a local MTA.
+    Smtp {
+        /// Relay address [default: localhost:25].
+        #[serde(default)]
+        server: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+    /// Runs a shell command with the notification in `PROWL_*` environment variables.
+    Command { command: String },
+}
+
+impl BackendConfig {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendConfig::Ntfy { .. } => "ntfy",
+            BackendConfig::Pushover { .. } => "pushover",
+            BackendConfig::Gotify { .. } => "gotify",
+            BackendConfig::Webhook { .. } => "webhook",
+            BackendConfig::Smtp { .. } => "smtp",
+            BackendConfig::Command { .. } => "command",
+        }
+    }
+}
+
+/// `[alertmanager]`: how Alertmanager webhook payloads are turned into notifications.
+///
+/// Templates substitute `{{status}}`, `{{count}}`, `{{labels.<name>}}` and
+/// `{{annotations.<name>}}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertmanagerConfig {
+    /// Event title for each group, rendered over the group's common labels and annotations.
+    #[serde(default)]
+    pub event_template: Option<String>,
+    /// One description line per alert, rendered over that alert's labels and annotations.
+    #[serde(default)]
+    pub description_template: Option<String>,
+    /// Priority for firing alerts by their `severity` label, overriding the defaults.
+    #[serde(default)]
+    pub severity_priorities: BTreeMap<String, Priority>,
+    /// Priority for resolved notices.
+    #[serde(default)]
+    pub resolved_priority: Option<Priority>,
+}
+
+/// `[adapters.<name>]`: a payload adapter that picks fields out of arbitrary JSON with
+/// JSON pointers (RFC 6901), e.g. `event = "/alert/title"`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JsonAdapterConfig {
+    #[serde(default)]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Maps values found at the `priority` pointer to priorities, for payloads that don't
+    /// use Prowl's names or numbers (e.g. `"true" = "high"`).
+    #[serde(default)]
+    pub priorities: BTreeMap<String, Priority>,
+}
+
+/// `[daemon]`: the Unix socket `prowl daemon` listens on and who may submit through it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonConfig {
+    #[serde(default)]
+    pub socket: Option<PathBuf>,
+    /// User IDs allowed to submit, besides the user running the daemon.
+    #[serde(default)]
+    pub allow_uids: Vec<u32>,
+    /// Primary group IDs allowed to submit. Supplementary groups aren't checked: a socket's
+    /// peer credentials don't include them.
+    #[serde(default)]
+    pub allow_gids: Vec<u32>,
+}
+
+/// `[schedule]`: how `prowl schedule run` treats notifications whose time has long passed,
+/// e.g. because the machine was off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    /// How late a notification may still go out as normal (e.g. "15m") [default: 1h].
+    #[serde(default)]
+    pub grace: Option<String>,
+    /// What to do with notifications later than `grace` [default: send].
+    #[serde(default)]
+    pub missed: Option<MissedPolicy>,
+}
+
+/// What `prowl schedule run` does with a notification later than the grace period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum MissedPolicy {
+    /// Send anyway, noting when it was due
+    #[default]
+    Send,
+    /// Drop it
+    Skip,
+}
+
+/// `[heartbeats.<name>]`: a job expected to run `prowl heartbeat ping <name>` at least once
+/// per `interval`; `prowl heartbeat check` notifies when it doesn't.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeartbeatConfig {
+    /// How often pings are expected (e.g. "1d").
+    pub interval: String,
+    /// How much later than `interval` a ping may arrive before it counts as missed
+    /// [default: 5m].
+    #[serde(default)]
+    pub grace: Option<String>,
+    /// Priority of the "missed" notification [default: emergency].
+    #[serde(default)]
+    pub priority: Option<Priority>,
+}
+
+/// `[checks.<name>]`: an HTTP endpoint that `prowl check` polls, notifying when it goes down
+/// and when it comes back.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CheckConfig {
+    pub url: String,
+    /// Status code the response must have [default: any 2xx].
+    #[serde(default)]
+    pub expect_status: Option<u16>,
+    /// Regular expression the response body must match.
+    #[serde(default)]
+    pub expect_body: Option<String>,
+    /// How long to wait for the response (e.g. "5s") [default: 10s].
+    #[serde(default)]
+    pub timeout: Option<String>,
+}
+
+/// `[history]`: the log of sent notifications that `prowl history` reads.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Set to false to stop recording sends [default: true].
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Size in bytes at which the history file is rotated [default: 5 MiB].
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    /// Rotated files to keep [default: 3].
+    #[serde(default)]
+    pub keep: Option<usize>,
+    /// Delete rotated files older than this (e.g. "90d") at the next rotation.
+    #[serde(default)]
+    pub retention: Option<String>,
+}
+
+/// `[[rules]]`: sender-side policy applied to every notification before it is sent. The
+/// first rule whose conditions all match decides what happens.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleConfig {
+    pub name: String,
+    /// Local time window such as "22:00-07:00"; windows may wrap past midnight.
+    #[serde(default)]
+    pub hours: Option<String>,
+    /// Weekdays ("mon", "tue", ...) on which the rule applies. For a window that wraps past
+    /// midnight, the early hours count as part of the previous day.
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Regular expression the event title must match.
+    #[serde(default)]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub application: Option<String>,
+    #[serde(default)]
+    pub min_priority: Option<Priority>,
+    #[serde(default)]
+    pub max_priority: Option<Priority>,
+    #[serde(default)]
+    pub action: RuleAction,
+    /// Priority to send at instead of the notification's own.
+    #[serde(default)]
+    pub set_priority: Option<Priority>,
+    /// API keys to send to instead of the notification's recipients.
+    #[serde(default)]
+    pub to: Vec<String>,
+}
+
+/// `[mail]`: how mail taken in by `prowl sendmail` and `prowl smtp` is addressed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MailConfig {
+    /// Recipient address, or just its local part (e.g. "root"), to registry labels, API
+    /// keys or prowl:// URLs, comma-separated. Other addresses go to the default key.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// Send `X-Priority: 1` mail as an emergency rather than high [default: false].
+    #[serde(default)]
+    pub emergency: Option<bool>,
+}
+
+/// `[[failover]]`: where notifications go when Prowl (or the profile's backend) can't deliver
+/// them. The first entry whose `min_priority` the notification meets applies.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FailoverConfig {
+    /// Least priority the chain applies to [default: all].
+    #[serde(default)]
+    pub min_priority: Option<Priority>,
+    /// Retries of the primary after a network failure, rate limit or server error, before
+    /// failing over [default: 2].
+    #[serde(default)]
+    pub retries: Option<u32>,
+    /// `[channels.<name>]` entries to try in order until one delivers.
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleAction {
+    /// Send, with any `set_priority` or `to` changes applied.
+    #[default]
+    Send,
+    Drop,
+    /// Hold the notification until the rule's `hours` window ends.
+    Delay,
 }
 
 impl Config {
@@ -48,6 +350,54 @@ impl Config {
             .map(|dirs| dirs.config_dir().join("config.toml"))
             .ok_or_else(|| ProwlError::Config("Could not determine config directory".to_string()))
     }
+
+    /// Sets `key` on the top-level config, or on a named profile when the key has the form
+    /// `profiles.<name>.<key>` (creating the profile if needed).
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<()> {
+        let (profile, key) = match key.strip_prefix("profiles.") {
+            Some(rest) => match rest.rsplit_once('.') {
+                Some((name, key)) if !name.is_empty() => (Some(name), key),
+                _ => {
+                    return Err(ProwlError::Config(format!(
+                        "Invalid profile key: {key}. Expected profiles.<name>.<key>"
+                    )));
+                }
+            },
+            None => (None, key),
+        };
+
+        let (api_key, provider_key, application) = match profile {
+            Some(name) => {
+                let profile = self.profiles.entry(name.to_string()).or_default();
+                (
+                    &mut profile.api_key,
+                    &mut profile.provider_key,
+                    &mut profile.application,
+                )
+            }
+            None => (
+                &mut self.api_key,
+                &mut self.provider_key,
+                &mut self.application,
+            ),
+        };
+
+        let slot = match key {
+            "api_key" => api_key,
+            "provider_key" => provider_key,
+            "application" => application,
+            _ => {
+                return Err(ProwlError::Config(format!(
+                    "Unknown config key: {key}. Valid keys are: api_key, provider_key, application"
+                )));
+            }
+        };
+        if key == "api_key" && Target::is_url(value) {
+            value.parse::<Target>()?;
+        }
+        *slot = Some(value.to_string());
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -55,6 +405,10 @@ pub struct ResolvedConfig {
     pub api_key: Option<String>,
     pub provider_key: Option<String>,
     pub application: String,
+    /// Default priority, from a target URL's `priority` parameter.
+    pub priority: Option<Priority>,
+    /// The selected profile's backend; `None` sends through Prowl.
+    pub backend: Option<BackendConfig>,
 }
 
 impl ResolvedConfig {
@@ -62,22 +416,50 @@ impl ResolvedConfig {
         cli_api_key: Option<&str>,
         cli_provider_key: Option<&str>,
         cli_application: Option<&str>,
+        cli_profile: Option<&str>,
     ) -> Result<Self> {
-        let file_config = Config::load().unwrap_or_default();
+        let mut file_config = Config::load().unwrap_or_default();
+
+        let profile = match cli_profile {
+            Some(name) => file_config
+                .profiles
+                .remove(name)
+                .ok_or_else(|| ProwlError::Config(format!("Unknown profile: {name}")))?,
+            None => Profile::default(),
+        };
 
         let api_key = cli_api_key
             .map(String::from)
             .or_else(|| std::env::var("PROWL_API_KEY").ok())
+            .or(profile.api_key)
             .or(file_config.api_key);
 
+        // A prowl:// URL in place of the key carries the other settings too, which then
+        // apply unless given as a flag or environment variable.
+        let target = api_key
+            .as_deref()
+            .filter(|key| Target::is_url(key))
+            .map(str::parse::<Target>)
+            .transpose()?;
+        let (api_key, target) = match target {
+            Some(target) => (Some(target.api_keys.join(",")), Some(target)),
+            None => (api_key, None),
+        };
+        let target_provider_key = target.as_ref().and_then(|t| t.provider_key.clone());
+        let target_application = target.as_ref().and_then(|t| t.application.clone());
+
         let provider_key = cli_provider_key
             .map(String::from)
             .or_else(|| std::env::var("PROWL_PROVIDER_KEY").ok())
+            .or(target_provider_key)
+            .or(profile.provider_key)
             .or(file_config.provider_key);
 
         let application = cli_application
             .map(String::from)
             .or_else(|| std::env::var("PROWL_APPLICATION").ok())
+            .or(target_application)
+            .or(profile.application)
             .or(file_config.application)
             .unwrap_or_else(|| "prowl-cli".to_string());
 
@@ -85,11 +467,23 @@ impl ResolvedConfig {
             api_key,
             provider_key,
             application,
+            priority: target.and_then(|target| target.priority),
+            backend: profile.backend,
         })
     }
 
+    /// The API key, which a profile with another backend doesn't need.
     pub fn require_api_key(&self) -> Result<&str> {
-        self.api_key.as_deref().ok_or(ProwlError::MissingApiKey)
+        match (&self.api_key, &self.backend) {
+            (Some(key), _) => Ok(key),
+            (None, Some(_)) => Ok(""),
+            (None, None) => Err(ProwlError::MissingApiKey),
+        }
+    }
+
+    /// The notifier for the selected profile's backend, if it has one.
+    pub fn notifier(&self) -> Result<Option<Arc<dyn Notifier>>> {
+        self.backend.as_ref().map(notifiers::build).transpose()
     }
 
     pub fn require_provider_key(&self) -> Result<&str> {
@@ -0,0 +1,237 @@
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::api::{ApiResponse, ProwlClient, SendRequest};
+use crate::error::{ProwlError, Result};
+use crate::output::{LogLevel, OutputFormatter, via_channel};
+use crate::schedule::{ScheduleStore, ScheduledNotification};
+use crate::throttle::ThrottleState;
+
+/// How long queued notifications may take to go out after a shutdown signal.
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many times a notification is retried after a network failure before it is dropped.
+const MAX_NETWORK_RETRIES: u32 = 3;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(600);
+
+/// What became of a notification passed to [`send`].
+#[derive(Debug)]
+pub enum Sent {
+    Delivered(ApiResponse),
+    /// A `delay` rule held it back; it's in the schedule for `prowl schedule run`.
+    Held(ScheduledNotification),
+}
+
+/// Sends `request` through `client`, adding it to the schedule if a rule delays it rather
+/// than keeping the caller waiting for the rule's window to end.
+pub async fn send(client: &ProwlClient, request: &SendRequest) -> Result<Sent> {
+    match client.send(request).await {
+        Ok(response) => Ok(Sent::Delivered(response)),
+        Err(ProwlError::Delayed { until, request, .. }) => {
+            let mut file = ScheduleStore::open()?;
+            let notification = file.data.add(until, *request);
+            file.save()?;
+            Ok(Sent::Held(notification))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Sends the `--summary` notifications for `--throttle` windows that have closed, for
+/// commands that run regularly but aren't throttled sends themselves.
+pub async fn flush_summaries(client: &ProwlClient, formatter: &dyn OutputFormatter) -> Result<()> {
+    let summaries = {
+        let mut file = ThrottleState::open()?;
+        let summaries = file.data.take_expired(SystemTime::now());
+        if summaries.is_empty() {
+            return Ok(());
+        }
+        file.save()?;
+        summaries
+    };
+    send_summaries(client, &summaries, formatter).await;
+    Ok(())
+}
+
+/// Sends "suppressed N repeats" summaries taken from the throttle state, warning about any
+/// that fail: their windows are gone, so they aren't retried.
+pub async fn send_summaries(
+    client: &ProwlClient,
+    summaries: &[SendRequest],
+    formatter: &dyn OutputFormatter,
+) {
+    for summary in summaries {
+        if let Err(e) = send(client, summary).await {
+            formatter.format_log(
+                LogLevel::Warning,
+                &format!(
+                    "Failed to send repeat summary for \"{}\": {e}",
+                    summary.event
+                ),
+            );
+        }
+    }
+}
+
+/// "Held \"Disk full\" as #3 until 2026-10-20 07:00", for log lines.
+pub fn held(notification: &ScheduledNotification) -> String {
+    format!(
+        "Held \"{}\" as #{} until {}",
+        notification.request.event,
+        notification.id,
+        notification.due().format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// Handle for queueing notifications from listener tasks; cheap to clone.
+#[derive(Clone)]
+pub struct DeliveryQueue {
+    sender: mpsc::Sender<SendRequest>,
+}
+
+impl DeliveryQueue {
+    /// Queues `request` without waiting. Returns false if the queue is full or the worker
+    /// has stopped.
+    pub fn try_enqueue(&self, request: SendRequest) -> bool {
+        self.sender.try_send(request).is_ok()
+    }
+}
+
+/// Queues `request` for a listener, or in a dry run only shows it. A full queue drops it
+/// with a warning rather than holding up the listener.
+pub fn deliver(
+    request: SendRequest,
+    queue: &DeliveryQueue,
+    dry_run: bool,
+    formatter: &dyn OutputFormatter,
+) {
+    if dry_run {
+        formatter.format_dry_run(&request);
+    } else if !queue.try_enqueue(request) {
+        formatter.format_log(
+            LogLevel::Warning,
+            "Delivery queue is full; dropped a notification",
+        );
+    }
+}
+
+/// Waits for a worker whose queue handles have all been dropped to send what's left, for
+/// up to [`DRAIN_TIMEOUT`]: once a shutdown signal has been handled, another won't stop
+/// the process, so a retry loop mustn't keep it running.
+pub async fn drain(worker: impl Future<Output = ()>, formatter: &dyn OutputFormatter) {
+    if tokio::time::timeout(DRAIN_TIMEOUT, worker).await.is_err() {
+        formatter.format_log(
+            LogLevel::Warning,
+            "Gave up on notifications still queued at shutdown",
+        );
+    }
+}
+
+/// Receiving end of a [`DeliveryQueue`], drained by [`DeliveryWorker::run`].
+pub struct DeliveryWorker {
+    receiver: mpsc::Receiver<SendRequest>,
+    client: ProwlClient,
+    pace: Duration,
+}
+
+pub fn queue(
+    capacity: usize,
+    client: ProwlClient,
+    pace: Duration,
+) -> (DeliveryQueue, DeliveryWorker) {
+    let (sender, receiver) = mpsc::channel(capacity);
+    (
+        DeliveryQueue { sender },
+        DeliveryWorker {
+            receiver,
+            client,
+            pace,
+        },
+    )
+}
+
+impl DeliveryWorker {
+    /// Shows queued notifications instead of sending them, for a dry run, until every
+    /// [`DeliveryQueue`] handle has been dropped and the queue is empty.
+    pub async fn show(mut self, formatter: &dyn OutputFormatter) {
+        while let Some(request) = self.receiver.recv().await {
+            formatter.format_dry_run(&request);
+        }
+    }
+
+    /// Sends queued notifications one at a time, at most one per `pace`, until every
+    /// [`DeliveryQueue`] handle has been dropped and the queue is empty.
+    ///
+    /// Rate-limited notifications are retried with backoff until they go through; network
+    /// failures are retried a few times; anything else the API rejects is logged and dropped.
+    pub async fn run(mut self, formatter: &dyn OutputFormatter) {
+        let mut last_send: Option<Instant> = None;
+
+        while let Some(request) = self.receiver.recv().await {
+            let mut network_retries = 0;
+            let mut retry_delay = INITIAL_RETRY_DELAY;
+
+            loop {
+                if let Some(last) = last_send {
+                    tokio::time::sleep_until(last + self.pace).await;
+                }
+                last_send = Some(Instant::now());
+
+                let error = match send(&self.client, &request).await {
+                    Ok(Sent::Held(notification)) => {
+                        formatter.format_log(LogLevel::Info, &held(&notification));
+                        break;
+                    }
+                    Ok(Sent::Delivered(resp)) => {
+                        let remaining = resp
+                            .remaining
+                            .map(|r| format!(" ({r} API calls remaining)"))
+                            .unwrap_or_default();
+                        formatter.format_log(
+                            LogLevel::Success,
+                            &format!(
+                                "Delivered \"{}\"{}{remaining}",
+                                request.event,
+                                via_channel(&resp)
+                            ),
+                        );
+                        break;
+                    }
+                    Err(e) => e,
+                };
+
+                let retry = match error {
+                    ProwlError::Api { code: 406, .. } | ProwlError::Backend { status: 429, .. } => {
+                        true
+                    }
+                    ProwlError::Http(_) if network_retries < MAX_NETWORK_RETRIES => {
+                        network_retries += 1;
+                        true
+                    }
+                    _ => false,
+                };
+
+                if !retry {
+                    formatter.format_log(
+                        LogLevel::Error,
+                        &format!("Dropped \"{}\": {error}", request.event),
+                    );
+                    break;
+                }
+
+                formatter.format_log(
+                    LogLevel::Warning,
+                    &format!(
+                        "Delivering \"{}\" failed ({error}); retrying in {}",
+                        request.event,
+                        humantime::format_duration(retry_delay)
+                    ),
+                );
+                tokio::time::sleep(retry_delay).await;
+                retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use chrono::{DateTime, Local};
 use thiserror::Error;
 
+use crate::api::SendRequest;
+
 #[derive(Debug, Error)]
 pub enum ProwlError {
     #[error("HTTP request failed: {0}")]
@@ -17,6 +20,14 @@ pub enum ProwlError {
     #[error("API error ({code}): {message}")]
     Api { code: i32, message: String },
 
+    /// A push service other than Prowl answered with an HTTP error.
+    #[error("{service} error ({status}): {message}")]
+    Backend {
+        service: String,
+        status: u16,
+        message: String,
+    },
+
     #[error("No API key provided. Set via --api-key, PROWL_API_KEY env var, or config file")]
     MissingApiKey,
 
@@ -29,8 +40,43 @@ pub enum ProwlError {
     #[error("Message too long: {length} bytes (max {max})")]
     MessageTooLong { length: usize, max: usize },
 
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Notification dropped by rule \"{rule}\"")]
+    DroppedByRule { rule: String },
+
+    /// Held back by a `delay` rule; `request`, as the rule left it, is due at `until`.
+    #[error("Notification delayed by rule \"{rule}\" until {}", until.format("%Y-%m-%d %H:%M"))]
+    Delayed {
+        rule: String,
+        until: DateTime<Local>,
+        request: Box<SendRequest>,
+    },
+
+    #[error("Delivery failed: {0}")]
+    Delivery(String),
+
+    #[error("{primary}; failover channels also failed: {failures}")]
+    FailoverFailed {
+        primary: Box<ProwlError>,
+        failures: String,
+    },
+
+    #[error("Delivered via failover channel \"{channel}\"")]
+    FailedOver { channel: String },
+
+    #[error("Daemon error: {0}")]
+    Daemon(String),
+
     #[error("Token not yet approved")]
     TokenNotApproved,
+
+    #[error("{failed} of {total} operations failed")]
+    PartialFailure { failed: usize, total: usize },
+
+    #[error("Timed out waiting for {condition}")]
+    WaitTimedOut { condition: String },
 }
 
 impl ProwlError {
@@ -56,9 +102,37 @@ impl ProwlError {
             },
             ProwlError::MissingApiKey | ProwlError::MissingProviderKey => 2,
             ProwlError::TokenNotApproved => 4,
+            ProwlError::DroppedByRule { .. } | ProwlError::Delayed { .. } => 5,
+            ProwlError::FailedOver { .. } => 6,
+            ProwlError::WaitTimedOut { .. } => 7,
+            ProwlError::FailoverFailed { primary, .. } => primary.exit_code(),
             _ => 1,
         }
     }
+
+    /// Whether the notification couldn't be delivered, as opposed to being invalid or
+    /// dropped, so that another channel might get it through.
+    pub fn is_delivery_failure(&self) -> bool {
+        matches!(
+            self,
+            ProwlError::Http(_)
+                | ProwlError::XmlParse(_)
+                | ProwlError::Io(_)
+                | ProwlError::Api { .. }
+                | ProwlError::Backend { .. }
+                | ProwlError::Delivery(_)
+        )
+    }
+
+    /// Whether the same send may succeed if tried again shortly.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ProwlError::Http(_) => true,
+            ProwlError::Api { code, .. } => matches!(code, 406 | 429 | 500..),
+            ProwlError::Backend { status, .. } => matches!(status, 429 | 500..),
+            _ => false,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, ProwlError>;
@@ -0,0 +1,167 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::api::{ApiResponse, SendRequest};
+use crate::config::Config;
+use crate::error::{ProwlError, Result};
+use crate::notifiers::{self, Notifier};
+
+/// Retries of the primary before failing over, unless configured.
+const DEFAULT_RETRIES: u32 = 2;
+
+/// Wait before the first retry of the primary; it doubles after each one.
+pub(crate) const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// The `[[failover]]` chains from the config file, with their channels built.
+#[derive(Clone, Default)]
+pub struct Failover {
+    chains: Vec<Chain>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Chain {
+    min_priority: i8,
+    pub(crate) retries: u32,
+    channels: Vec<Channel>,
+}
+
+#[derive(Clone)]
+struct Channel {
+    name: String,
+    notifier: Arc<dyn Notifier>,
+}
+
+impl Failover {
+    pub fn compile(config: &Config) -> Result<Self> {
+        let chains = config
+            .failover
+            .iter()
+            .map(|failover| {
+                let channels = failover
+                    .channels
+                    .iter()
+                    .map(|name| {
+                        let channel = config.channels.get(name).ok_or_else(|| {
+                            ProwlError::Config(format!(
+                                "Unknown failover channel \"{name}\"; define it under \
+                                 [channels.{name}]"
+                            ))
+                        })?;
+                        Ok(Channel {
+                            name: name.clone(),
+                            notifier: notifiers::build(channel)?,
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(Chain {
+                    min_priority: failover.min_priority.map_or(i8::MIN, |p| p.as_i8()),
+                    retries: failover.retries.unwrap_or(DEFAULT_RETRIES),
+                    channels,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Failover { chains })
+    }
+
+    /// Builds the failover chains from the config file.
+    pub fn load() -> Result<Self> {
+        Self::compile(&Config::load()?)
+    }
+
+    /// The chain for notifications at `priority`, if any.
+    pub(crate) fn chain(&self, priority: i8) -> Option<&Chain> {
+        self.chains
+            .iter()
+            .find(|chain| priority >= chain.min_priority)
+    }
+}
+
+impl Chain {
+    /// Tries each channel in turn after the primary failed with `primary`.
+    pub(crate) async fn deliver(
+        &self,
+        request: &SendRequest,
+        primary: ProwlError,
+    ) -> Result<ApiResponse> {
+        let mut failures = Vec::new();
+        for channel in &self.channels {
+            match channel.notifier.notify(request).await {
+                Ok(response) => return Ok(response.via_failover(&channel.name, &primary)),
+                Err(e) => failures.push(format!("{}: {e}", channel.name)),
+            }
+        }
+        if failures.is_empty() {
+            return Err(primary);
+        }
+        Err(ProwlError::FailoverFailed {
+            primary: Box::new(primary),
+            failures: failures.join("; "),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ProwlClient;
+    use crate::config::{BackendConfig, FailoverConfig};
+    use crate::notifiers::stand_in;
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_fails_over_for_urgent_notifications() {
+        let (primary_url, primary) = stand_in::serve(503, "down for maintenance");
+        let (fallback_url, fallback) = stand_in::serve(200, "{}");
+        let webhook = |url: String| BackendConfig::Webhook {
+            url,
+            headers: BTreeMap::new(),
+        };
+        let config = Config {
+            failover: vec![FailoverConfig {
+                min_priority: Some(crate::api::Priority::High),
+                retries: Some(0),
+                channels: vec!["broken".to_string(), "pager".to_string()],
+            }],
+            channels: BTreeMap::from([
+                (
+                    "broken".to_string(),
+                    BackendConfig::Command {
+                        command: "exit 1".to_string(),
+                    },
+                ),
+                ("pager".to_string(), webhook(fallback_url)),
+            ]),
+            ..Config::default()
+        };
+        let client = ProwlClient::new()
+            .unwrap()
+            .with_backend(Some(notifiers::build(&webhook(primary_url)).unwrap()))
+            .with_failover(Failover::compile(&config).unwrap());
+
+        let request = SendRequest {
+            apikey: String::new(),
+            application: "db1".to_string(),
+            event: "Disk full".to_string(),
+            priority: 2,
+            ..SendRequest::example()
+        };
+        let response = client.send(&request).await.unwrap();
+        assert_eq!(response.channel.as_deref(), Some("pager"));
+        assert!(response.error_message.unwrap().contains("503"));
+        primary.join().unwrap();
+        fallback.join().unwrap();
+
+        // Below the chain's minimum priority, the primary's error stands.
+        let (primary_url, primary) = stand_in::serve(503, "down for maintenance");
+        let client = client.with_backend(Some(notifiers::build(&webhook(primary_url)).unwrap()));
+        let request = SendRequest {
+            priority: 0,
+            ..request
+        };
+        assert!(matches!(
+            client.send(&request).await,
+            Err(ProwlError::Backend { status: 503, .. })
+        ));
+        primary.join().unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::api::Priority;
+use crate::config::Config;
+use crate::error::{ProwlError, Result};
+use crate::rules::local_time;
+use crate::store::LockedStore;
+
+/// Grace period for heartbeats that don't configure one.
+const DEFAULT_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// A `[heartbeats.<name>]` entry from the config file, validated.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    pub name: String,
+    pub interval: Duration,
+    pub grace: Duration,
+    /// Priority of the "missed" notification.
+    pub priority: Priority,
+}
+
+impl Heartbeat {
+    /// Every heartbeat in the config file, by name.
+    pub fn load_all(config: &Config) -> Result<Vec<Heartbeat>> {
+        config
+            .heartbeats
+            .iter()
+            .map(|(name, heartbeat)| {
+                let parse = |field: &str, value: &str| {
+                    humantime::parse_duration(value).map_err(|e| {
+                        ProwlError::Config(format!("Heartbeat \"{name}\": invalid {field}: {e}"))
+                    })
+                };
+                Ok(Heartbeat {
+                    name: name.clone(),
+                    interval: parse("interval", &heartbeat.interval)?,
+                    grace: heartbeat
+                        .grace
+                        .as_deref()
+                        .map(|grace| parse("grace", grace))
+                        .transpose()?
+                        .unwrap_or(DEFAULT_GRACE),
+                    priority: heartbeat.priority.unwrap_or(Priority::Emergency),
+                })
+            })
+            .collect()
+    }
+}
+
+/// When each heartbeat was last pinged and whether it is overdue.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HeartbeatState {
+    #[serde(default)]
+    pub beats: BTreeMap<String, BeatState>,
+}
+
+/// Times are Unix seconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BeatState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_ping: Option<i64>,
+    /// When monitoring started; a heartbeat that has never been pinged is due one interval
+    /// after this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watched_since: Option<i64>,
+    /// When the heartbeat became overdue, once the "missed" notification has gone out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub down_since: Option<i64>,
+}
+
+/// A heartbeat as shown by `prowl heartbeat list`.
+#[derive(Debug, Clone)]
+pub struct HeartbeatStatus {
+    pub name: String,
+    pub interval: Duration,
+    pub last_ping: Option<DateTime<Local>>,
+    /// When it will be reported missed; `None` until it has been pinged or checked.
+    pub deadline: Option<DateTime<Local>>,
+    /// Whether it has been reported missed and hasn't recovered yet.
+    pub down: bool,
+}
+
+/// A change worth notifying about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// No ping arrived by `deadline`.
+    Missed {
+        last_ping: Option<i64>,
+        deadline: i64,
+    },
+    /// A ping arrived after the heartbeat was reported as missed.
+    Recovered { down_since: i64, pinged: i64 },
+}
+
+/// The heartbeat state file, locked while in use.
+pub type HeartbeatFile = LockedStore<HeartbeatState>;
+
+impl HeartbeatState {
+    pub fn open() -> Result<HeartbeatFile> {
+        LockedStore::open("heartbeats.toml", "heartbeat state")
+    }
+
+    pub fn ping(&mut self, name: &str, now: DateTime<Local>) {
+        self.beats.entry(name.to_string()).or_default().last_ping = Some(now.timestamp());
+    }
+
+    /// Starts the clock for heartbeats seen for the first time.
+    pub fn watch(&mut self, heartbeat: &Heartbeat, now: DateTime<Local>) {
+        let beat = self.beats.entry(heartbeat.name.clone()).or_default();
+        beat.watched_since.get_or_insert(now.timestamp());
+    }
+
+    /// When `heartbeat` is next due to be reported missed, if it is being watched.
+    pub fn deadline(&self, heartbeat: &Heartbeat) -> Option<i64> {
+        let beat = self.beats.get(&heartbeat.name)?;
+        let since = beat.last_ping.or(beat.watched_since)?;
+        Some(since + seconds(heartbeat.interval) + seconds(heartbeat.grace))
+    }
+
+    pub fn transition(&self, heartbeat: &Heartbeat, now: DateTime<Local>) -> Option<Transition> {
+        let beat = self.beats.get(&heartbeat.name)?;
+        let deadline = self.deadline(heartbeat)?;
+        let overdue = now.timestamp() > deadline;
+
+        match (overdue, beat.down_since) {
+            (true, None) => Some(Transition::Missed {
+                last_ping: beat.last_ping,
+                deadline,
+            }),
+            (false, Some(down_since)) => Some(Transition::Recovered {
+                down_since,
+                pinged: beat.last_ping.unwrap_or(deadline),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self, heartbeat: &Heartbeat) -> HeartbeatStatus {
+        let beat = self.beats.get(&heartbeat.name);
+        HeartbeatStatus {
+            name: heartbeat.name.clone(),
+            interval: heartbeat.interval,
+            last_ping: beat.and_then(|beat| beat.last_ping).map(local_time),
+            deadline: self.deadline(heartbeat).map(local_time),
+            down: beat.is_some_and(|beat| beat.down_since.is_some()),
+        }
+    }
+
+    /// Records that the notification for `transition` went out.
+    pub fn record(&mut self, name: &str, transition: Transition) {
+        let beat = self.beats.entry(name.to_string()).or_default();
+        beat.down_since = match transition {
+            Transition::Missed { deadline, .. } => Some(deadline),
+            Transition::Recovered { .. } => None,
+        };
+    }
+}
+
+fn seconds(duration: Duration) -> i64 {
+    i64::try_from(duration.as_secs()).unwrap_or(i64::MAX / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missed_then_recovered() {
+        let heartbeat = Heartbeat {
+            name: "backup".to_string(),
+            interval: Duration::from_secs(3600),
+            grace: Duration::from_secs(600),
+            priority: Priority::Emergency,
+        };
+        let start = local_time(1_800_000_000);
+        let after = |secs: i64| start + chrono::Duration::seconds(secs);
+        let mut state = HeartbeatState::default();
+
+        state.watch(&heartbeat, start);
+        assert_eq!(state.transition(&heartbeat, after(4200)), None);
+        let missed = state.transition(&heartbeat, after(4201)).unwrap();
+        assert_eq!(
+            missed,
+            Transition::Missed {
+                last_ping: None,
+                deadline: start.timestamp() + 4200
+            }
+        );
+
+        // Until the notification is recorded as sent, the next check tries again.
+        assert_eq!(state.transition(&heartbeat, after(4300)), Some(missed));
+        state.record("backup", missed);
+        assert_eq!(state.transition(&heartbeat, after(4300)), None);
+
+        state.ping("backup", after(5000));
+        let recovered = state.transition(&heartbeat, after(5060)).unwrap();
+        assert_eq!(
+            recovered,
+            Transition::Recovered {
+                down_since: start.timestamp() + 4200,
+                pinged: start.timestamp() + 5000,
+            }
+        );
+        state.record("backup", recovered);
+        assert_eq!(state.transition(&heartbeat, after(5060)), None);
+        assert_eq!(
+            state.deadline(&heartbeat),
+            Some(start.timestamp() + 5000 + 4200)
+        );
+    }
+}
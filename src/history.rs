@@ -0,0 +1,386 @@
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::adapters::truncate;
+use crate::api::{ApiResponse, SendRequest};
+
+use crate::config::Config;
+use crate::error::{ProwlError, Result};
+use crate::registry::KeyRegistry;
+use crate::store::data_path;
+use crate::target::{expand_keys, mask};
+
+/// Size at which the history file is rotated, unless configured.
+const DEFAULT_MAX_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept, unless configured.
+const DEFAULT_KEEP: usize = 3;
+
+/// Longest description kept in an entry.
+const MAX_DESCRIPTION_LEN: usize = 200;
+
+/// One attempted send, as a line of `history.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub time: DateTime<Local>,
+    pub application: String,
+    pub event: String,
+    /// The first part of the description.
+    pub description: String,
+    pub priority: i8,
+    /// Recipients by profile or registry label where known, otherwise as masked keys.
+    pub recipients: Vec<String>,
+    /// The backend the notification went through, when not Prowl.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+    pub status: SendStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a send, as recorded in the history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum SendStatus {
+    /// Accepted by the API
+    Sent,
+    /// Rejected by the API or never delivered
+    Failed,
+    /// Dropped by a [[rules]] entry
+    Dropped,
+    /// Held back by a [[rules]] entry to be sent later
+    Delayed,
+}
+
+/// Appends every send a client makes to the history file in the data directory.
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+    max_size: u64,
+    keep: usize,
+    retention: Option<Duration>,
+    /// Names for API keys, from the config file's profiles and the key registry.
+    labels: HashMap<String, String>,
+}
+
+impl History {
+    /// The history settings from the config file, or `None` when `[history] enabled = false`.
+    pub fn load() -> Result<Option<Self>> {
+        Self::from_config(&Config::load()?)
+    }
+
+    /// The history settings from `config`, or `None` when `[history] enabled = false`.
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let settings = config.history.clone().unwrap_or_default();
+        if settings.enabled == Some(false) {
+            return Ok(None);
+        }
+
+        let retention = settings
+            .retention
+            .as_deref()
+            .map(|retention| {
+                humantime::parse_duration(retention).map_err(|e| {
+                    ProwlError::Config(format!("Invalid [history] retention \"{retention}\": {e}"))
+                })
+            })
+            .transpose()?;
+
+        let mut labels = HashMap::new();
+        // Labels are only for display, so a broken registry mustn't stop anything sending.
+        let registry = KeyRegistry::load().unwrap_or_else(|_e| {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Not labelling history with the key registry: {_e}");
+            KeyRegistry::default()
+        });
+        for (label, user) in registry.users {
+            labels.insert(user.api_key, label);
+        }
+        for (name, profile) in &config.profiles {
+            if let Some(key) = &profile.api_key {
+                for key in expand_keys([key])? {
+                    labels.insert(key, name.clone());
+                }
+            }
+        }
+        if let Some(key) = &config.api_key {
+            for key in expand_keys([key])? {
+                labels.insert(key, "default".to_string());
+            }
+        }
+
+        Ok(Some(History {
+            path: data_path("history.jsonl")?,
+            max_size: settings.max_size.unwrap_or(DEFAULT_MAX_SIZE),
+            keep: settings.keep.unwrap_or(DEFAULT_KEEP),
+            retention,
+            labels,
+        }))
+    }
+
+    /// Records the outcome of sending `request`, through the backend named `via` if not
+    /// Prowl.
+    pub fn record(
+        &self,
+        request: &SendRequest,
+        via: Option<&str>,
+        result: &Result<ApiResponse>,
+    ) -> Result<()> {
+        let mut description = request.description.clone();
+        truncate(&mut description, MAX_DESCRIPTION_LEN);
+
+        let (status, code, remaining, error) = match result {
+            // A failover delivery keeps the primary's error.
+            Ok(response) => (
+                SendStatus::Sent,
+                Some(response.code),
+                response.remaining,
+                response.error_message.clone(),
+            ),
+            Err(ProwlError::DroppedByRule { .. }) => {
+                (SendStatus::Dropped, None, None, result_error(result))
+            }
+            Err(ProwlError::Delayed { .. }) => {
+                (SendStatus::Delayed, None, None, result_error(result))
+            }
+            Err(ProwlError::Api { code, .. }) => {
+                (SendStatus::Failed, Some(*code), None, result_error(result))
+            }
+            Err(ProwlError::Backend { status, .. }) => (
+                SendStatus::Failed,
+                Some(i32::from(*status)),
+                None,
+                result_error(result),
+            ),
+            Err(_) => (SendStatus::Failed, None, None, result_error(result)),
+        };
+
+        let entry = HistoryEntry {
+            time: Local::now(),
+            application: request.application.clone(),
+            event: request.event.clone(),
+            description,
+            priority: request.priority,
+            recipients: request
+                .apikey
+                .split(',')
+                .map(|key| key.trim())
+                .filter(|key| !key.is_empty())
+                .map(|key| self.labels.get(key).cloned().unwrap_or_else(|| mask(key)))
+                .collect(),
+            via: via.map(str::to_string),
+            status,
+            code,
+            remaining,
+            error,
+        };
+
+        self.append(&entry)
+    }
+
+    fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = File::options().create(true).append(true).open(&self.path)?;
+        file.lock()?;
+
+        let mut line = serde_json::to_string(entry)
+            .map_err(|e| ProwlError::Config(format!("Failed to serialize history: {e}")))?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+
+        if file.metadata()?.len() >= self.max_size {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+
+    /// Shifts `history.jsonl` to `history.jsonl.1` and so on, dropping files beyond `keep`
+    /// and any older than the retention period. Runs with the current file locked.
+    fn rotate(&self) -> Result<()> {
+        let rotated = |n: usize| rotated_path(&self.path, n);
+
+        remove_if_exists(&rotated(self.keep))?;
+        for n in (1..self.keep).rev() {
+            if rotated(n).exists() {
+                std::fs::rename(rotated(n), rotated(n + 1))?;
+            }
+        }
+        if self.keep == 0 {
+            remove_if_exists(&self.path)?;
+        } else {
+            std::fs::rename(&self.path, rotated(1))?;
+        }
+
+        if let Some(retention) = self.retention {
+            let cutoff = SystemTime::now() - retention;
+            for n in 1..=self.keep {
+                let path = rotated(n);
+                if let Ok(modified) = path.metadata().and_then(|meta| meta.modified())
+                    && modified < cutoff
+                {
+                    remove_if_exists(&path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which history entries `prowl history` shows.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub since: Option<DateTime<Local>>,
+    pub status: Option<SendStatus>,
+    pub event: Option<Regex>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.since.is_none_or(|since| entry.time >= since)
+            && self.status.is_none_or(|status| entry.status == status)
+            && self
+                .event
+                .as_ref()
+                .is_none_or(|event| event.is_match(&entry.event))
+    }
+}
+
+/// Reads the history, oldest first, across rotated files. Lines that don't parse, e.g. a
+/// write cut short, are skipped.
+pub fn read(filter: &HistoryFilter) -> Result<Vec<HistoryEntry>> {
+    let path = data_path("history.jsonl")?;
+
+    let mut files: Vec<PathBuf> = (1..)
+        .map(|n| rotated_path(&path, n))
+        .take_while(|path| path.exists())
+        .collect();
+    files.reverse();
+    files.push(path);
+
+    let mut entries = Vec::new();
+    for path in files {
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        for line in BufReader::new(file).lines() {
+            if let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line?)
+                && filter.matches(&entry)
+            {
+                entries.push(entry);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{n}"));
+    PathBuf::from(name)
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+fn result_error(result: &Result<ApiResponse>) -> Option<String> {
+    result.as_ref().err().map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(dir: &Path, max_size: u64) -> History {
+        History {
+            path: dir.join("history.jsonl"),
+            max_size,
+            keep: 2,
+            retention: None,
+            labels: HashMap::from([("a".repeat(40), "phone".to_string())]),
+        }
+    }
+
+    fn request(event: &str) -> SendRequest {
+        SendRequest {
+            apikey: format!("{},{}", "a".repeat(40), "b".repeat(40)),
+            event: event.to_string(),
+            description: "x".repeat(500),
+            priority: 2,
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_record_and_rotate() {
+        let dir = std::env::temp_dir().join(format!("prowl-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let history = history(&dir, 1500);
+
+        history
+            .record(
+                &request("db down"),
+                None,
+                &Ok(ApiResponse::success(200, Some(999), None)),
+            )
+            .unwrap();
+        history
+            .record(
+                &request("db down"),
+                Some("ntfy"),
+                &Err(ProwlError::Api {
+                    code: 401,
+                    message: "Invalid API key".to_string(),
+                }),
+            )
+            .unwrap();
+
+        let contents = std::fs::read_to_string(dir.join("history.jsonl")).unwrap();
+        let entries: Vec<HistoryEntry> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].status, SendStatus::Sent);
+        assert_eq!(entries[0].remaining, Some(999));
+        assert_eq!(entries[0].recipients, ["phone", "bbbb...bbbb"]);
+        assert!(entries[0].description.len() <= MAX_DESCRIPTION_LEN);
+        assert_eq!(entries[1].status, SendStatus::Failed);
+        assert_eq!(entries[1].code, Some(401));
+        assert_eq!(entries[1].via.as_deref(), Some("ntfy"));
+
+        // Each entry is ~400 bytes, so a few more push the file past 1500 and rotate it.
+        for _ in 0..6 {
+            history
+                .record(
+                    &request("noise"),
+                    None,
+                    &Ok(ApiResponse::success(200, None, None)),
+                )
+                .unwrap();
+        }
+        assert!(dir.join("history.jsonl.1").exists());
+        assert!(!dir.join("history.jsonl.3").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
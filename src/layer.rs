@@ -0,0 +1,337 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{self, Write as _};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use tracing::callsite::Identifier;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::adapters::truncate;
+use crate::api::{Priority, ProwlClient, SendRequest};
+use crate::error::Result;
+use crate::panic_hook::default_application;
+
+/// Default minimum time between notifications from the same callsite.
+const DEFAULT_RATE_LIMIT: Duration = Duration::from_secs(60);
+
+/// Default number of notifications waiting to be sent before new ones are dropped.
+const DEFAULT_QUEUE_SIZE: usize = 64;
+
+thread_local! {
+    /// Set on the delivery thread, so events logged while sending (e.g. by the HTTP stack)
+    /// don't turn into more notifications.
+    static ON_DELIVERY_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A `tracing_subscriber` layer that sends events at or above a level as notifications.
+///
+/// ```no_run
+/// use tracing_subscriber::prelude::*;
+///
+/// let layer = prowl::ProwlLayer::new("API_KEY")?
+///     .with_application("billing")
+///     .with_level(tracing::Level::WARN);
+/// tracing_subscriber::registry().with(layer).init();
+/// # Ok::<(), prowl::error::ProwlError>(())
+/// ```
+///
+/// Events are queued and sent from a background thread, so logging never waits on the
+/// network; when the queue is full, or the same callsite already sent a notification within
+/// the rate limit, the event is skipped. Notifications still queued when the process exits
+/// are lost.
+///
+/// Levels map onto priorities as ERROR → high, WARN → normal, INFO → moderate, and DEBUG
+/// and TRACE → very low.
+pub struct ProwlLayer {
+    client: ProwlClient,
+    template: SendRequest,
+    level: Level,
+    rate_limit: Duration,
+    queue_size: usize,
+    sender: OnceLock<Option<SyncSender<SendRequest>>>,
+    last_sent: Mutex<HashMap<Identifier, Instant>>,
+}
+
+impl ProwlLayer {
+    /// A layer sending ERROR events to `api_key` (comma-separated for several recipients).
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        Ok(ProwlLayer {
+            client: ProwlClient::new()?,
+            template: SendRequest {
+                apikey: api_key.into(),
+                application: default_application(),
+                event: String::new(),
+                description: String::new(),
+                priority: 0,
+                url: None,
+                providerkey: None,
+            },
+            level: Level::ERROR,
+            rate_limit: DEFAULT_RATE_LIMIT,
+            queue_size: DEFAULT_QUEUE_SIZE,
+            sender: OnceLock::new(),
+            last_sent: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Application name shown in notifications [default: the executable's name].
+    pub fn with_application(mut self, application: impl Into<String>) -> Self {
+        self.template.application = application.into();
+        self
+    }
+
+    pub fn with_provider_key(mut self, provider_key: impl Into<String>) -> Self {
+        self.template.providerkey = Some(provider_key.into());
+        self
+    }
+
+    /// Least severe level that is sent [default: ERROR].
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Minimum time between notifications from the same callsite [default: 60s].
+    pub fn with_rate_limit(mut self, rate_limit: Duration) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Notifications that may wait to be sent before new ones are dropped [default: 64].
+    pub fn with_queue_size(mut self, queue_size: usize) -> Self {
+        self.queue_size = queue_size.max(1);
+        self
+    }
+
+    /// Sends through `client`, e.g. one with rules or history attached.
+    pub fn with_client(mut self, client: ProwlClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Whether the callsite may send now, recording the send if so.
+    fn allow(&self, callsite: Identifier, now: Instant) -> bool {
+        let Ok(mut last_sent) = self.last_sent.lock() else {
+            return false;
+        };
+        if let Some(last) = last_sent.get(&callsite)
+            && now.duration_since(*last) < self.rate_limit
+        {
+            return false;
+        }
+        last_sent.insert(callsite, now);
+        true
+    }
+
+    fn enqueue(&self, request: SendRequest) {
+        let sender = self.sender.get_or_init(|| {
+            let (sender, receiver) = mpsc::sync_channel(self.queue_size);
+            let client = self.client.clone();
+            std::thread::Builder::new()
+                .name("prowl-layer".to_string())
+                .spawn(move || deliver(client, receiver))
+                .ok()
+                .map(|_| sender)
+        });
+        if let Some(sender) = sender {
+            let _ = sender.try_send(request);
+        }
+    }
+
+    fn build_request<S>(&self, event: &Event<'_>, ctx: &Context<'_, S>) -> SendRequest
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let metadata = event.metadata();
+        let mut fields = FieldVisitor::default();
+        event.record(&mut fields);
+
+        let mut title = fields
+            .message
+            .take()
+            .unwrap_or_else(|| format!("{} event", metadata.level()));
+        truncate(&mut title, SendRequest::MAX_EVENT_LEN);
+
+        let mut description = format!("{} {}", metadata.level(), metadata.target());
+        if let (Some(file), Some(line)) = (metadata.file(), metadata.line()) {
+            let _ = write!(description, " at {file}:{line}");
+        }
+        if !fields.fields.is_empty() {
+            let _ = write!(description, "\n{}", fields.fields);
+        }
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let _ = write!(description, "\nin {}", span.name());
+                if let Some(SpanFields(fields)) = span.extensions().get::<SpanFields>()
+                    && !fields.is_empty()
+                {
+                    let _ = write!(description, " {{{fields}}}");
+                }
+            }
+        }
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        SendRequest {
+            event: title,
+            description,
+            priority: level_priority(*metadata.level()).as_i8(),
+            ..self.template.clone()
+        }
+    }
+}
+
+impl<S> Layer<S> for ProwlLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = FieldVisitor::default();
+        attrs.record(&mut fields);
+        span.extensions_mut()
+            .insert(SpanFields(fields.into_string()));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = FieldVisitor::default();
+        values.record(&mut fields);
+        let recorded = fields.into_string();
+        if let Some(SpanFields(existing)) = span.extensions_mut().get_mut::<SpanFields>() {
+            if !existing.is_empty() && !recorded.is_empty() {
+                existing.push_str(", ");
+            }
+            existing.push_str(&recorded);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // Level ordering is by verbosity: ERROR is the "smallest".
+        if *event.metadata().level() > self.level || ON_DELIVERY_THREAD.get() {
+            return;
+        }
+        if !self.allow(event.metadata().callsite(), Instant::now()) {
+            return;
+        }
+        self.enqueue(self.build_request(event, &ctx));
+    }
+}
+
+/// Priority for events at `level`.
+pub fn level_priority(level: Level) -> Priority {
+    match level {
+        Level::ERROR => Priority::High,
+        Level::WARN => Priority::Normal,
+        Level::INFO => Priority::Moderate,
+        _ => Priority::VeryLow,
+    }
+}
+
+fn deliver(client: ProwlClient, receiver: Receiver<SendRequest>) {
+    ON_DELIVERY_THREAD.set(true);
+    let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return;
+    };
+    while let Ok(request) = receiver.recv() {
+        // There's nowhere to report a failure without logging, which could loop back here.
+        let _ = runtime.block_on(client.send(&request));
+    }
+}
+
+/// A span's fields, formatted as `name=value` pairs.
+struct SpanFields(String);
+
+/// Collects an event's `message` and its other fields as `name=value` pairs.
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: String,
+}
+
+impl FieldVisitor {
+    fn into_string(self) -> String {
+        match self.message {
+            Some(message) if self.fields.is_empty() => format!("message={message}"),
+            Some(message) => format!("message={message}, {}", self.fields),
+            None => self.fields,
+        }
+    }
+
+    fn push(&mut self, field: &Field, value: fmt::Arguments<'_>) {
+        if field.name() == "message" {
+            self.message = Some(value.to_string());
+            return;
+        }
+        if !self.fields.is_empty() {
+            self.fields.push_str(", ");
+        }
+        let _ = write!(self.fields, "{}={}", field.name(), value);
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.push(field, format_args!("{value:?}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_events_become_rate_limited_requests() {
+        let layer = ProwlLayer::new("key")
+            .unwrap()
+            .with_application("billing")
+            .with_level(Level::WARN);
+        let (sender, receiver) = mpsc::sync_channel(8);
+        layer.sender.set(Some(sender)).ok();
+
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("charge", customer = "c-42");
+            let _entered = span.enter();
+            for attempt in 0..3 {
+                tracing::error!(attempt, "payment failed");
+            }
+            tracing::info!("below the level");
+            tracing::warn!(queue = "invoices", "backlog growing");
+        });
+
+        let requests: Vec<SendRequest> = receiver.try_iter().collect();
+        assert_eq!(requests.len(), 2);
+
+        assert_eq!(requests[0].event, "payment failed");
+        assert_eq!(requests[0].application, "billing");
+        assert_eq!(requests[0].priority, 1);
+        assert!(requests[0].description.starts_with("ERROR "));
+        assert!(requests[0].description.contains("\nattempt=0\n"));
+        assert!(
+            requests[0]
+                .description
+                .ends_with("\nin charge {customer=c-42}")
+        );
+
+        assert_eq!(requests[1].event, "backlog growing");
+        assert_eq!(requests[1].priority, 0);
+    }
+}
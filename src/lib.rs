@@ -0,0 +1,40 @@
+pub mod adapters;
+pub mod api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+#[cfg(feature = "cli")]
+pub mod check;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+pub mod commands;
+pub mod config;
+#[cfg(feature = "cli")]
+pub mod delivery;
+pub mod error;
+pub mod failover;
+pub mod heartbeat;
+pub mod history;
+#[cfg(feature = "tracing")]
+pub mod layer;
+#[cfg(feature = "cli")]
+pub mod mail;
+pub mod notifiers;
+#[cfg(feature = "cli")]
+pub mod output;
+pub mod panic_hook;
+pub mod registry;
+pub mod rules;
+#[cfg(feature = "cli")]
+pub mod schedule;
+#[cfg(all(feature = "cli", unix))]
+pub mod socket;
+pub mod store;
+#[cfg(feature = "cli")]
+pub mod syslog;
+pub mod target;
+#[cfg(feature = "cli")]
+pub mod throttle;
+
+#[cfg(feature = "tracing")]
+pub use layer::ProwlLayer;
@@ -0,0 +1,582 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+use crate::adapters::truncate;
+use crate::api::{Priority, SendRequest};
+use crate::config::Config;
+use crate::error::{ProwlError, Result};
+use crate::registry::KeyRegistry;
+use crate::target::{Target, expand_keys, is_api_key, recipient_keys};
+
+/// An RFC 2047 encoded word, e.g. `=?UTF-8?B?SGVsbG8=?=`.
+static ENCODED_WORD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"=\?([^?\s]+)\?([BbQq])\?([^?\s]*)\?=").expect("valid regex"));
+
+/// Whitespace between two encoded words, which isn't part of the text.
+static BETWEEN_WORDS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\?=\s+=\?").expect("valid regex"));
+
+static HTML_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<(script|style)\b.*?</(script|style)>|<[^>]*>").expect("valid regex")
+});
+
+/// Deepest nesting of multipart bodies that is searched for text.
+const MAX_DEPTH: usize = 8;
+
+/// The parts of an email message that make up a notification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Mail {
+    pub from: Option<String>,
+    /// Addresses from the To, Cc and Bcc headers.
+    pub recipients: Vec<String>,
+    pub subject: Option<String>,
+    /// The first text part, decoded; HTML is reduced to its text.
+    pub body: String,
+    /// From `X-Priority`, or failing that `Importance`.
+    pub priority: Option<Priority>,
+    /// Whether `X-Priority` is 1 (Highest), which is sent as high unless
+    /// [`Mail::with_emergency`] says otherwise.
+    pub highest: bool,
+}
+
+impl Mail {
+    /// Parses an RFC 5322 message, leniently: anything unreadable is left out rather than
+    /// failing the message.
+    pub fn parse(raw: &[u8]) -> Self {
+        let entity = Entity::parse(raw);
+        let header = |name: &str| entity.header(name).map(decode_words);
+
+        let mut recipients = Vec::new();
+        for name in ["to", "cc", "bcc"] {
+            for value in entity.headers_named(name) {
+                recipients.extend(addresses(value));
+            }
+        }
+
+        let x_priority = entity.header("x-priority");
+        let priority = x_priority
+            .and_then(x_priority_level)
+            .or_else(|| entity.header("importance").and_then(importance));
+
+        Mail {
+            from: header("from"),
+            recipients,
+            subject: header("subject").filter(|subject| !subject.trim().is_empty()),
+            body: entity
+                .text(0)
+                .unwrap_or_default()
+                .replace("\r\n", "\n")
+                .trim()
+                .to_string(),
+            priority,
+            highest: x_priority.is_some_and(|value| value.trim().starts_with('1')),
+        }
+    }
+
+    /// Sends `X-Priority: 1` mail as an emergency if `enabled` (`[mail] emergency`).
+    pub fn with_emergency(mut self, enabled: bool) -> Self {
+        if enabled && self.highest {
+            self.priority = Some(Priority::Emergency);
+        }
+        self
+    }
+
+    /// The notification for this message, taking the API key, application and defaults
+    /// from `template`.
+    pub fn request(&self, template: &SendRequest) -> SendRequest {
+        let mut event = match (&self.subject, &self.from) {
+            (Some(subject), _) => subject.trim().to_string(),
+            (None, Some(from)) => format!("Mail from {from}"),
+            (None, None) => "(no subject)".to_string(),
+        };
+        truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+
+        let mut description = self.body.clone();
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        SendRequest {
+            event,
+            description,
+            priority: self.priority.map_or(template.priority, Priority::as_i8),
+            ..template.clone()
+        }
+    }
+}
+
+/// `[mail] aliases`: which API keys mail for each address goes to.
+#[derive(Debug, Clone, Default)]
+pub struct Aliases {
+    aliases: BTreeMap<String, String>,
+    registry: KeyRegistry,
+}
+
+impl Aliases {
+    pub fn load(config: &Config) -> Result<Self> {
+        let aliases = config
+            .mail
+            .as_ref()
+            .map(|mail| {
+                mail.aliases
+                    .iter()
+                    .map(|(address, target)| (address.to_ascii_lowercase(), target.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Only aliases naming registry labels need the registry, so one that can't be read
+        // doesn't stop mail that is addressed by key.
+        let registry = if uses_labels(&aliases) {
+            KeyRegistry::load()?
+        } else {
+            KeyRegistry::default()
+        };
+        Ok(Aliases { aliases, registry })
+    }
+
+    /// API keys (comma-separated) for mail to `recipients`. An address matches an alias by
+    /// the whole address, then by its local part; addresses without one, or no addresses
+    /// at all, go to `default`.
+    pub fn resolve(&self, recipients: &[String], default: Option<&str>) -> Result<String> {
+        let mut keys: Vec<String> = Vec::new();
+        let mut needs_default = recipients.is_empty();
+
+        for recipient in recipients {
+            let recipient = recipient.to_ascii_lowercase();
+            let local_part = recipient.split('@').next().unwrap_or(&recipient);
+            match self
+                .aliases
+                .get(&recipient)
+                .or_else(|| self.aliases.get(local_part))
+            {
+                Some(target) => keys.extend(self.expand(target)?),
+                None => needs_default = true,
+            }
+        }
+
+        if needs_default {
+            let default = default.ok_or(ProwlError::MissingApiKey)?;
+            keys.extend(expand_keys([&default.to_string()])?);
+        }
+
+        let mut unique = Vec::new();
+        for key in keys {
+            if !unique.contains(&key) {
+                unique.push(key);
+            }
+        }
+        Ok(unique.join(","))
+    }
+
+    /// Keys for an alias: registry labels, API keys or target URLs, comma-separated.
+    fn expand(&self, target: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        for item in target
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            match self.registry.users.get(item) {
+                Some(user) => keys.push(user.api_key.clone()),
+                None => keys.extend(recipient_keys([&item.to_string()])?),
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// Whether any alias names something other than an API key or target URL, which can only
+/// be a registry label.
+fn uses_labels(aliases: &BTreeMap<String, String>) -> bool {
+    aliases
+        .values()
+        .flat_map(|target| target.split(','))
+        .map(str::trim)
+        .any(|item| !item.is_empty() && !Target::is_url(item) && !is_api_key(item))
+}
+
+/// A message or one part of a multipart body.
+struct Entity<'a> {
+    headers: Vec<(String, String)>,
+    body: &'a [u8],
+}
+
+impl<'a> Entity<'a> {
+    fn parse(raw: &'a [u8]) -> Self {
+        let (head, body) = split_head(raw);
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in String::from_utf8_lossy(head).lines() {
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+        Entity { headers, body }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers_named(name).next()
+    }
+
+    fn headers_named(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.headers
+            .iter()
+            .filter(move |(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The entity's text, from the first text/plain part, or else the first HTML part.
+    fn text(&self, depth: usize) -> Option<String> {
+        let content_type = self.header("content-type").unwrap_or("text/plain");
+        let (mime, params) = parse_content_type(content_type);
+
+        if self
+            .header("content-disposition")
+            .is_some_and(|disposition| disposition.to_ascii_lowercase().starts_with("attachment"))
+        {
+            return None;
+        }
+
+        if mime.starts_with("multipart/") {
+            if depth >= MAX_DEPTH {
+                return None;
+            }
+            let boundary = params.get("boundary")?;
+            let parts: Vec<Entity> = split_parts(self.body, boundary)
+                .into_iter()
+                .map(Entity::parse)
+                .collect();
+            let is_plain = |part: &Entity| {
+                let content_type = part.header("content-type").unwrap_or("text/plain");
+                parse_content_type(content_type).0 == "text/plain"
+            };
+            return parts
+                .iter()
+                .filter(|part| is_plain(part))
+                .chain(parts.iter().filter(|part| !is_plain(part)))
+                .find_map(|part| part.text(depth + 1));
+        }
+
+        let html = match mime.as_str() {
+            "text/plain" => false,
+            "text/html" => true,
+            _ => return None,
+        };
+
+        let encoding = self
+            .header("content-transfer-encoding")
+            .unwrap_or("7bit")
+            .trim()
+            .to_ascii_lowercase();
+        let bytes = match encoding.as_str() {
+            "base64" => {
+                let compact: Vec<u8> = self
+                    .body
+                    .iter()
+                    .copied()
+                    .filter(|b| !b.is_ascii_whitespace())
+                    .collect();
+                STANDARD.decode(compact).ok()?
+            }
+            "quoted-printable" => decode_quoted_printable(self.body, false),
+            _ => self.body.to_vec(),
+        };
+
+        let text = decode_charset(&bytes, params.get("charset").map(String::as_str));
+        Some(if html { html_text(&text) } else { text })
+    }
+}
+
+/// Splits a message at the blank line ending its headers.
+fn split_head(raw: &[u8]) -> (&[u8], &[u8]) {
+    let crlf = raw.windows(4).position(|w| w == b"\r\n\r\n");
+    let lf = raw.windows(2).position(|w| w == b"\n\n");
+    match (crlf, lf) {
+        (Some(crlf), Some(lf)) if lf + 1 < crlf => (&raw[..lf], &raw[lf + 2..]),
+        (Some(crlf), _) => (&raw[..crlf], &raw[crlf + 4..]),
+        (None, Some(lf)) => (&raw[..lf], &raw[lf + 2..]),
+        (None, None) => (raw, &[]),
+    }
+}
+
+/// The parts between `--boundary` lines, up to the closing `--boundary--`.
+fn split_parts<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{boundary}");
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut offset = 0;
+
+    for line in body.split_inclusive(|&b| b == b'\n') {
+        let trimmed = line.trim_ascii_end();
+        if trimmed.starts_with(delimiter.as_bytes()) {
+            if let Some(start) = start {
+                // The line break before a delimiter belongs to the delimiter.
+                let end = if offset >= 2 && &body[offset - 2..offset] == b"\r\n" {
+                    offset - 2
+                } else {
+                    offset.saturating_sub(1).max(start)
+                };
+                parts.push(&body[start..end]);
+            }
+            if trimmed[delimiter.len()..].starts_with(b"--") {
+                break;
+            }
+            start = Some(offset + line.len());
+        }
+        offset += line.len();
+    }
+    parts
+}
+
+/// The lowercased MIME type and its parameters, with quotes removed.
+fn parse_content_type(value: &str) -> (String, BTreeMap<String, String>) {
+    let mut pieces = value.split(';');
+    let mime = pieces.next().unwrap_or("").trim().to_ascii_lowercase();
+    let params = pieces
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| {
+            (
+                name.trim().to_ascii_lowercase(),
+                value.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    (mime, params)
+}
+
+/// Decodes RFC 2047 encoded words in a header value.
+pub fn decode_words(value: &str) -> String {
+    let value = BETWEEN_WORDS.replace_all(value, "?==?");
+    ENCODED_WORD
+        .replace_all(&value, |captures: &regex::Captures| {
+            let charset = &captures[1];
+            let text = captures[3].as_bytes();
+            let bytes = if captures[2].eq_ignore_ascii_case("b") {
+                STANDARD.decode(text).ok()
+            } else {
+                Some(decode_quoted_printable(text, true))
+            };
+            match bytes {
+                Some(bytes) => decode_charset(&bytes, Some(charset)),
+                None => captures[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Decodes quoted-printable text; in headers (`header`), "_" also stands for a space.
+fn decode_quoted_printable(text: &[u8], header: bool) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < text.len() {
+        match text[i] {
+            b'=' if text[i + 1..].starts_with(b"\r\n") => i += 3,
+            b'=' if text[i + 1..].starts_with(b"\n") => i += 2,
+            b'=' => match text
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 3;
+                }
+                None => {
+                    decoded.push(b'=');
+                    i += 1;
+                }
+            },
+            b'_' if header => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Text in `charset`; Latin-1 and Windows-1252 are converted, anything else is read as
+/// UTF-8.
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    match charset.map(str::to_ascii_lowercase).as_deref() {
+        Some("iso-8859-1" | "latin1" | "latin-1" | "windows-1252" | "cp1252") => {
+            bytes.iter().map(|&b| b as char).collect()
+        }
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// The text of an HTML body: tags, scripts and styles removed, common entities decoded
+/// and blank lines collapsed.
+fn html_text(html: &str) -> String {
+    let text = HTML_TAG.replace_all(html, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    lines.join("\n")
+}
+
+/// The addresses in an address-list header, e.g. `"Ops, Team" <ops@example.com>, root`.
+pub fn addresses(value: &str) -> Vec<String> {
+    let mut entries = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut angle = false;
+    let mut comment = 0usize;
+
+    for c in value.chars() {
+        match c {
+            '"' if comment == 0 => quoted = !quoted,
+            '(' if !quoted => comment += 1,
+            ')' if !quoted && comment > 0 => comment -= 1,
+            '<' if !quoted && comment == 0 => angle = true,
+            '>' if !quoted && comment == 0 => angle = false,
+            ',' if !quoted && !angle && comment == 0 => {
+                entries.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    entries.push(current);
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let address = match (entry.find('<'), entry.rfind('>')) {
+                (Some(start), Some(end)) if start < end => entry[start + 1..end].to_string(),
+                _ => strip_comments(entry),
+            };
+            let address = address.trim().to_string();
+            (!address.is_empty()).then_some(address)
+        })
+        .collect()
+}
+
+fn strip_comments(value: &str) -> String {
+    let mut depth = 0usize;
+    value
+        .chars()
+        .filter(|&c| match c {
+            '(' => {
+                depth += 1;
+                false
+            }
+            ')' if depth > 0 => {
+                depth -= 1;
+                false
+            }
+            _ => depth == 0,
+        })
+        .collect()
+}
+
+/// `X-Priority: 1 (Highest)` through `5 (Lowest)`. Mail clients mark anything important
+/// as 1, so it's only high; see [`Mail::with_emergency`].
+fn x_priority_level(value: &str) -> Option<Priority> {
+    match value.trim().chars().next()? {
+        '1' | '2' => Some(Priority::High),
+        '3' => Some(Priority::Normal),
+        '4' => Some(Priority::Moderate),
+        '5' => Some(Priority::VeryLow),
+        _ => None,
+    }
+}
+
+/// `Importance: high`, `normal` or `low`.
+fn importance(value: &str) -> Option<Priority> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "high" => Some(Priority::High),
+        "normal" => Some(Priority::Normal),
+        "low" => Some(Priority::Moderate),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multipart_message() {
+        let raw = concat!(
+            "From: Cron Daemon <root@db1>\r\n",
+            "To: \"Ops, Team\" <ops@example.com>,\r\n",
+            "\troot (local admin)\r\n",
+            "Subject: =?UTF-8?B?QmFja3VwIGZhaWxlZA==?= =?UTF-8?Q?_on_db1_=E2=9C=97?=\r\n",
+            "X-Priority: 2 (High)\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/alternative; boundary=\"b1\"\r\n",
+            "\r\n",
+            "preamble\r\n",
+            "--b1\r\n",
+            "Content-Type: text/html; charset=utf-8\r\n",
+            "\r\n",
+            "<p>HTML version</p>\r\n",
+            "--b1\r\n",
+            "Content-Type: text/plain; charset=iso-8859-1\r\n",
+            "Content-Transfer-Encoding: quoted-printable\r\n",
+            "\r\n",
+            "pg_dump exited with 1 =E0 03:00, see=\r\n",
+            " /var/log/backup.log\r\n",
+            "--b1--\r\n",
+        );
+        let mail = Mail::parse(raw.as_bytes());
+        assert_eq!(mail.from.as_deref(), Some("Cron Daemon <root@db1>"));
+        assert_eq!(mail.recipients, ["ops@example.com", "root"]);
+        assert_eq!(mail.subject.as_deref(), Some("Backup failed on db1 ✗"));
+        assert_eq!(
+            mail.body,
+            "pg_dump exited with 1 à 03:00, see /var/log/backup.log"
+        );
+        assert_eq!(mail.priority, Some(Priority::High));
+        assert!(!mail.highest);
+
+        let highest = Mail::parse(b"Subject: RAID degraded\nX-Priority: 1 (Highest)\n\nmd0");
+        assert_eq!(highest.priority, Some(Priority::High));
+        assert_eq!(
+            highest.clone().with_emergency(true).priority,
+            Some(Priority::Emergency)
+        );
+        assert_eq!(highest.with_emergency(false).priority, Some(Priority::High));
+        assert_eq!(mail.with_emergency(true).priority, Some(Priority::High));
+
+        let html = Mail::parse(
+            b"Subject: x\nImportance: low\nContent-Type: text/html\n\n<b>Disk</b> &amp; <i>CPU</i>",
+        );
+        assert_eq!(html.body, "Disk & CPU");
+        assert_eq!(html.priority, Some(Priority::Moderate));
+    }
+
+    #[test]
+    fn test_uses_labels() {
+        let key = "0123456789abcdef0123456789abcdef01234567";
+        let aliases = BTreeMap::from([
+            ("root".to_string(), format!("{key}, prowl://{key}@")),
+            ("backups".to_string(), key.to_string()),
+        ]);
+        assert!(!uses_labels(&aliases));
+
+        let mut with_label = aliases.clone();
+        with_label.insert("ops".to_string(), format!("alice,{key}"));
+        assert!(uses_labels(&with_label));
+    }
+}
@@ -1,29 +1,26 @@
-mod api;
-mod cli;
-mod commands;
-mod config;
-mod error;
-mod output;
-
 use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use std::io;
 
-use cli::{Cli, Command};
-use config::ResolvedConfig;
-use output::get_formatter;
+use prowl::cli::{Cli, Command};
+use prowl::config::ResolvedConfig;
+use prowl::output::{self, get_formatter};
+use prowl::{commands, error};
 
 #[tokio::main]
 async fn main() {
     color_eyre::install().ok();
 
-    let cli = Cli::parse();
+    let cli = Cli::parse_from(commands::sendmail::argv(std::env::args_os()));
     let formatter = get_formatter(cli.format);
 
     let result = run(cli, formatter.as_ref()).await;
 
     if let Err(e) = result {
-        formatter.format_error(&e);
+        // Already reported as a (failover) success.
+        if !matches!(e, error::ProwlError::FailedOver { .. }) {
+            formatter.format_error(&e);
+        }
         std::process::exit(e.exit_code());
     }
 }
@@ -33,13 +30,28 @@ async fn run(cli: Cli, formatter: &dyn output::OutputFormatter) -> error::Result
         cli.api_key.as_deref(),
         cli.provider_key.as_deref(),
         cli.application.as_deref(),
+        cli.profile.as_deref(),
     )?;
 
     match &cli.command {
         Command::Send(args) => commands::send::execute(args, &config, formatter).await,
-        Command::Verify => commands::verify::execute(&config, formatter).await,
-        Command::Token => commands::token::execute(&config, formatter).await,
+        Command::Verify(args) => commands::verify::execute(args, &config, formatter).await,
+        Command::Token(args) => commands::token::execute(args, &config, formatter).await,
         Command::Register(args) => commands::register::execute(args, &config, formatter).await,
+        Command::Users(cmd) => commands::users::execute(cmd, &config, formatter).await,
+        Command::Serve(args) => commands::serve::execute(args, &config, formatter).await,
+        #[cfg(unix)]
+        Command::Daemon(args) => commands::daemon::execute(args, &config, formatter).await,
+        Command::Syslog(args) => commands::syslog::execute(args, &config, formatter).await,
+        Command::Smtp(args) => commands::smtp::execute(args, &config, formatter).await,
+        Command::Adapt(args) => commands::adapt::execute(args, &config, formatter).await,
+        Command::Schedule(cmd) => commands::schedule::execute(cmd, &config, formatter).await,
+        Command::Heartbeat(cmd) => commands::heartbeat::execute(cmd, &config, formatter).await,
+        Command::Check(args) => commands::check::execute(args, &config, formatter).await,
+        Command::Wait(args) => commands::wait::execute(args, &config, formatter).await,
+        Command::History(args) => commands::history::execute(args, formatter),
+        Command::Sendmail(args) => commands::sendmail::execute(args, &config, formatter).await,
+        Command::Rules(cmd) => commands::rules::execute(cmd, &config, formatter),
         Command::Config(cmd) => commands::config_cmd::execute(cmd, formatter),
         Command::Completions { shell } => {
             let mut cmd = Cli::command();
@@ -0,0 +1,109 @@
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::{BoxFuture, Notifier};
+use crate::adapters::truncate;
+use crate::api::{ApiResponse, SendRequest};
+use crate::error::{ProwlError, Result};
+
+/// Longest stderr excerpt kept in an error message.
+const MAX_STDERR_LEN: usize = 200;
+
+/// Longest a command may run before it's killed and the delivery counted as failed.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs a shell command for each notification, passing it in `PROWL_APPLICATION`,
+/// `PROWL_EVENT`, `PROWL_DESCRIPTION`, `PROWL_PRIORITY` (-2..2) and `PROWL_URL`. A non-zero
+/// exit status fails the delivery.
+pub struct CommandNotifier {
+    command: String,
+}
+
+impl CommandNotifier {
+    pub fn new(command: String) -> Self {
+        CommandNotifier { command }
+    }
+
+    async fn run(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let output = shell(&self.command)
+            .env("PROWL_APPLICATION", &request.application)
+            .env("PROWL_EVENT", &request.event)
+            .env("PROWL_DESCRIPTION", &request.description)
+            .env("PROWL_PRIORITY", request.priority.to_string())
+            .env("PROWL_URL", request.url.as_deref().unwrap_or(""))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output();
+        let output = tokio::time::timeout(TIMEOUT, output).await.map_err(|_| {
+            ProwlError::Delivery(format!(
+                "\"{}\" timed out after {}s",
+                self.command,
+                TIMEOUT.as_secs()
+            ))
+        })??;
+
+        if output.status.success() {
+            return Ok(ApiResponse::success(0, None, None));
+        }
+        let mut stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        truncate(&mut stderr, MAX_STDERR_LEN);
+        Err(ProwlError::Delivery(if stderr.is_empty() {
+            format!("\"{}\" {}", self.command, output.status)
+        } else {
+            format!("\"{}\" {}: {stderr}", self.command, output.status)
+        }))
+    }
+}
+
+impl Notifier for CommandNotifier {
+    fn name(&self) -> &str {
+        "command"
+    }
+
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+        Box::pin(self.run(request))
+    }
+}
+
+/// Builds a command that runs `command` through the platform shell.
+#[cfg(unix)]
+pub(crate) fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+pub(crate) fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_command_sees_notification() {
+        let request = SendRequest {
+            apikey: String::new(),
+            application: "db1".to_string(),
+            event: "Disk full".to_string(),
+            priority: 2,
+            ..SendRequest::example()
+        };
+        let check = r#"test "$PROWL_EVENT $PROWL_PRIORITY" = "Disk full 2""#;
+        CommandNotifier::new(check.to_string())
+            .notify(&request)
+            .await
+            .unwrap();
+
+        let failing = CommandNotifier::new("echo no modem >&2; exit 3".to_string());
+        let error = failing.notify(&request).await.unwrap_err().to_string();
+        assert!(error.contains("no modem"), "{error}");
+    }
+}
@@ -0,0 +1,96 @@
+use reqwest::Client;
+use serde_json::json;
+
+use super::{BoxFuture, Notifier, deliver, message};
+use crate::api::{ApiResponse, SendRequest};
+use crate::error::Result;
+
+/// Sends to a [Gotify](https://gotify.net) server.
+///
+/// Priorities -2..2 map onto Gotify's 0..10 scale as 1, 3, 5, 8 and 10, so that high and
+/// emergency notifications make a sound on the Android app; the url opens on click.
+pub struct GotifyNotifier {
+    client: Client,
+    server: String,
+    token: String,
+}
+
+impl GotifyNotifier {
+    pub fn new(client: Client, server: String, token: String) -> Self {
+        GotifyNotifier {
+            client,
+            server: server.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    async fn post(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let mut body = json!({
+            "title": request.event,
+            "message": message(request),
+            "priority": priority(request.priority),
+        });
+        if let Some(ref url) = request.url {
+            body["extras"] = json!({ "client::notification": { "click": { "url": url } } });
+        }
+
+        let builder = self
+            .client
+            .post(format!("{}/message", self.server))
+            .header("X-Gotify-Key", &self.token);
+        deliver("Gotify", super::json(builder, &body)).await
+    }
+}
+
+impl Notifier for GotifyNotifier {
+    fn name(&self) -> &str {
+        "gotify"
+    }
+
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+        Box::pin(self.post(request))
+    }
+}
+
+fn priority(priority: i8) -> u8 {
+    match priority {
+        ..=-2 => 1,
+        -1 => 3,
+        0 => 5,
+        1 => 8,
+        _ => 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifiers::stand_in;
+
+    #[tokio::test]
+    async fn test_post_message() {
+        let (url, server) = stand_in::serve(200, r#"{"id":25}"#);
+        let notifier = GotifyNotifier::new(Client::new(), format!("{url}/"), "A1b2".to_string());
+        let request = SendRequest {
+            apikey: String::new(),
+            application: "deploy".to_string(),
+            event: "Deploy finished".to_string(),
+            description: "v1.4.2 is live".to_string(),
+            priority: 1,
+            url: Some("https://example.com/deploys/9".to_string()),
+            providerkey: None,
+        };
+        notifier.notify(&request).await.unwrap();
+
+        let raw = server.join().unwrap();
+        assert!(raw.starts_with("POST /message "));
+        assert!(raw.to_ascii_lowercase().contains("x-gotify-key: a1b2"));
+        let body: serde_json::Value = serde_json::from_str(stand_in::body(&raw)).unwrap();
+        assert_eq!(body["priority"], 8);
+        assert_eq!(body["message"], "v1.4.2 is live");
+        assert_eq!(
+            body["extras"]["client::notification"]["click"]["url"],
+            "https://example.com/deploys/9"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+pub mod command;
+pub mod gotify;
+pub mod ntfy;
+pub mod pushover;
+pub mod smtp;
+pub mod webhook;
+
+use reqwest::header::{CONTENT_TYPE, HeaderValue};
+use reqwest::{Client, RequestBuilder};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::adapters::truncate;
+use crate::api::{ApiResponse, SendRequest};
+use crate::config::BackendConfig;
+use crate::error::{ProwlError, Result};
+
+pub use command::CommandNotifier;
+pub use gotify::GotifyNotifier;
+pub use ntfy::NtfyNotifier;
+pub use pushover::PushoverNotifier;
+pub use smtp::SmtpNotifier;
+pub use webhook::WebhookNotifier;
+
+/// Longest error response body kept in an error message.
+const MAX_ERROR_LEN: usize = 200;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A push service that notifications can be sent through.
+pub trait Notifier: Send + Sync {
+    /// The service's name, e.g. "ntfy".
+    fn name(&self) -> &str;
+
+    /// Delivers `request`, mapping its priority and url onto the service's equivalents. The
+    /// request has already been validated; a response outside 2xx is a
+    /// [`ProwlError::Backend`] carrying the HTTP status.
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>>;
+}
+
+/// Builds the notifier for a profile's `backend` table or a `[channels.<name>]` entry.
+pub fn build(config: &BackendConfig) -> Result<Arc<dyn Notifier>> {
+    let client = http_client()?;
+    Ok(match config.clone() {
+        BackendConfig::Ntfy {
+            server,
+            topic,
+            token,
+        } => Arc::new(NtfyNotifier::new(client, server, topic, token)),
+        BackendConfig::Pushover {
+            token,
+            user,
+            device,
+            server,
+        } => Arc::new(PushoverNotifier::new(client, server, token, user, device)),
+        BackendConfig::Gotify { server, token } => {
+            Arc::new(GotifyNotifier::new(client, server, token))
+        }
+        BackendConfig::Webhook { url, headers } => {
+            Arc::new(WebhookNotifier::new(client, url, headers)?)
+        }
+        BackendConfig::Smtp { server, from, to } => Arc::new(SmtpNotifier::new(server, from, to)),
+        BackendConfig::Command { command } => Arc::new(CommandNotifier::new(command)),
+    })
+}
+
+fn http_client() -> Result<Client> {
+    Ok(Client::builder()
+        .user_agent(format!("prowl-cli/{}", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+/// The notification's body text. Services that require one get the title when the
+/// description is empty.
+fn message(request: &SendRequest) -> &str {
+    if request.description.is_empty() {
+        &request.event
+    } else {
+        &request.description
+    }
+}
+
+/// Attaches `value` as a JSON body.
+fn json(builder: RequestBuilder, value: &serde_json::Value) -> RequestBuilder {
+    builder
+        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .body(value.to_string())
+}
+
+/// Sends `builder`, treating any status outside 2xx as an error from `service`.
+async fn deliver(service: &str, builder: RequestBuilder) -> Result<ApiResponse> {
+    let response = builder.send().await?;
+    let status = response.status();
+    let body = response.text().await?;
+
+    if status.is_success() {
+        return Ok(ApiResponse::success(status.as_u16().into(), None, None));
+    }
+
+    let mut detail = body.trim().to_string();
+    if detail.is_empty() {
+        detail = status
+            .canonical_reason()
+            .unwrap_or("request failed")
+            .to_string();
+    }
+    truncate(&mut detail, MAX_ERROR_LEN);
+    Err(ProwlError::Backend {
+        service: service.to_string(),
+        status: status.as_u16(),
+        message: detail,
+    })
+}
+
+/// Stand-ins for push services in tests.
+#[cfg(test)]
+pub(crate) mod stand_in {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use std::thread::JoinHandle;
+
+    use super::{BoxFuture, Notifier};
+    use crate::api::{ApiResponse, SendRequest};
+    use crate::error::Result;
+
+    /// Serves one request with `status` and `body` from a background thread, returning the
+    /// server's base URL and a handle resolving to the raw request it received.
+    pub fn serve(status: u16, body: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            while !complete(&request) {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            let response = format!(
+                "HTTP/1.1 {status} Stand-in\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        (url, handle)
+    }
+
+    /// Whether `request` holds the headers and the whole body they announce.
+    fn complete(request: &[u8]) -> bool {
+        let text = String::from_utf8_lossy(request);
+        let Some((head, body)) = text.split_once("\r\n\r\n") else {
+            return false;
+        };
+        let length = head
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .unwrap_or(0);
+        body.len() >= length
+    }
+
+    /// The body of a raw request.
+    pub fn body(request: &str) -> &str {
+        request.split_once("\r\n\r\n").map_or("", |(_, body)| body)
+    }
+
+    /// A backend that accepts every notification and keeps it.
+    #[derive(Default)]
+    pub struct Recorder(Mutex<Vec<SendRequest>>);
+
+    impl Recorder {
+        pub fn received(&self) -> Vec<SendRequest> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl Notifier for Recorder {
+        fn name(&self) -> &str {
+            "recorder"
+        }
+
+        fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+            self.0.lock().unwrap().push(request.clone());
+            Box::pin(async { Ok(ApiResponse::success(200, None, None)) })
+        }
+    }
+}
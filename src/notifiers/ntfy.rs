@@ -0,0 +1,114 @@
+use reqwest::Client;
+use serde_json::json;
+
+use super::{BoxFuture, Notifier, deliver, message};
+use crate::api::{ApiResponse, SendRequest};
+use crate::error::Result;
+
+const DEFAULT_SERVER: &str = "https://ntfy.sh";
+
+/// Publishes to a topic on an [ntfy](https://ntfy.sh) server.
+///
+/// Priorities -2..2 map onto ntfy's 1 (min) to 5 (max), the url becomes the click action,
+/// and the application is added as a tag.
+pub struct NtfyNotifier {
+    client: Client,
+    server: String,
+    topic: String,
+    token: Option<String>,
+}
+
+impl NtfyNotifier {
+    pub fn new(
+        client: Client,
+        server: Option<String>,
+        topic: String,
+        token: Option<String>,
+    ) -> Self {
+        NtfyNotifier {
+            client,
+            server: server
+                .as_deref()
+                .unwrap_or(DEFAULT_SERVER)
+                .trim_end_matches('/')
+                .to_string(),
+            topic,
+            token,
+        }
+    }
+
+    async fn publish(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let mut body = json!({
+            "topic": self.topic,
+            "title": request.event,
+            "message": message(request),
+            "priority": priority(request.priority),
+            "tags": [request.application],
+        });
+        if let Some(ref url) = request.url {
+            body["click"] = json!(url);
+        }
+
+        let mut builder = self.client.post(format!("{}/", self.server));
+        if let Some(ref token) = self.token {
+            builder = builder.bearer_auth(token);
+        }
+        deliver("ntfy", super::json(builder, &body)).await
+    }
+}
+
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &str {
+        "ntfy"
+    }
+
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+        Box::pin(self.publish(request))
+    }
+}
+
+fn priority(priority: i8) -> i8 {
+    priority.clamp(-2, 2) + 3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifiers::stand_in;
+
+    #[tokio::test]
+    async fn test_publish() {
+        let (url, server) = stand_in::serve(200, r#"{"id":"x1"}"#);
+        let notifier = NtfyNotifier::new(
+            Client::new(),
+            Some(url),
+            "alerts".to_string(),
+            Some("tk_secret".to_string()),
+        );
+        let request = SendRequest {
+            apikey: String::new(),
+            application: "backup".to_string(),
+            event: "Backup failed".to_string(),
+            description: "disk full".to_string(),
+            priority: 2,
+            url: Some("https://example.com/runs/7".to_string()),
+            providerkey: None,
+        };
+
+        let response = notifier.notify(&request).await.unwrap();
+        assert_eq!(response.code, 200);
+
+        let raw = server.join().unwrap();
+        assert!(raw.starts_with("POST / "));
+        assert!(
+            raw.to_ascii_lowercase()
+                .contains("authorization: bearer tk_secret")
+        );
+        let body: serde_json::Value = serde_json::from_str(stand_in::body(&raw)).unwrap();
+        assert_eq!(body["topic"], "alerts");
+        assert_eq!(body["title"], "Backup failed");
+        assert_eq!(body["message"], "disk full");
+        assert_eq!(body["priority"], 5);
+        assert_eq!(body["click"], "https://example.com/runs/7");
+    }
+}
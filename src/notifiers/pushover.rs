@@ -0,0 +1,145 @@
+use reqwest::Client;
+
+use super::{BoxFuture, Notifier, deliver, message};
+use crate::api::{ApiResponse, SendRequest};
+use crate::error::Result;
+
+const DEFAULT_SERVER: &str = "https://api.pushover.net";
+
+/// Seconds between repeats of an emergency notification until it is acknowledged.
+const EMERGENCY_RETRY: &str = "60";
+
+/// Seconds after which an unacknowledged emergency notification stops repeating.
+const EMERGENCY_EXPIRE: &str = "3600";
+
+/// Sends through [Pushover](https://pushover.net).
+///
+/// Priorities map one to one, Pushover using the same -2..2 scale; emergency notifications
+/// repeat every minute for an hour until acknowledged.
+pub struct PushoverNotifier {
+    client: Client,
+    server: String,
+    token: String,
+    user: String,
+    device: Option<String>,
+}
+
+impl PushoverNotifier {
+    pub fn new(
+        client: Client,
+        server: Option<String>,
+        token: String,
+        user: String,
+        device: Option<String>,
+    ) -> Self {
+        PushoverNotifier {
+            client,
+            server: server
+                .as_deref()
+                .unwrap_or(DEFAULT_SERVER)
+                .trim_end_matches('/')
+                .to_string(),
+            token,
+            user,
+            device,
+        }
+    }
+
+    async fn push(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let priority = request.priority.clamp(-2, 2);
+        let mut form = vec![
+            ("token", self.token.clone()),
+            ("user", self.user.clone()),
+            ("title", request.event.clone()),
+            ("message", message(request).to_string()),
+            ("priority", priority.to_string()),
+        ];
+        if let Some(ref url) = request.url {
+            form.push(("url", url.clone()));
+        }
+        if let Some(ref device) = self.device {
+            form.push(("device", device.clone()));
+        }
+        if priority == 2 {
+            form.push(("retry", EMERGENCY_RETRY.to_string()));
+            form.push(("expire", EMERGENCY_EXPIRE.to_string()));
+        }
+
+        let builder = self
+            .client
+            .post(format!("{}/1/messages.json", self.server))
+            .form(&form);
+        deliver("Pushover", builder).await
+    }
+}
+
+impl Notifier for PushoverNotifier {
+    fn name(&self) -> &str {
+        "pushover"
+    }
+
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+        Box::pin(self.push(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ProwlError;
+    use crate::notifiers::stand_in;
+
+    fn request(priority: i8) -> SendRequest {
+        SendRequest {
+            apikey: String::new(),
+            application: "backup".to_string(),
+            event: "Backup failed".to_string(),
+            priority,
+            ..SendRequest::example()
+        }
+    }
+
+    fn notifier(url: String) -> PushoverNotifier {
+        PushoverNotifier::new(
+            Client::new(),
+            Some(url),
+            "app-token".to_string(),
+            "user-key".to_string(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_push_emergency() {
+        let (url, server) = stand_in::serve(200, r#"{"status":1,"request":"r1"}"#);
+        notifier(url).notify(&request(2)).await.unwrap();
+
+        let raw = server.join().unwrap();
+        assert!(raw.starts_with("POST /1/messages.json "));
+        let body = stand_in::body(&raw);
+        for field in [
+            "token=app-token",
+            "user=user-key",
+            "message=Backup+failed",
+            "priority=2",
+            "retry=60",
+            "expire=3600",
+        ] {
+            assert!(
+                body.split('&').any(|pair| pair == field),
+                "{field} in {body}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rejected_push() {
+        let (url, server) = stand_in::serve(400, r#"{"user":"invalid","status":0}"#);
+        let result = notifier(url).notify(&request(0)).await;
+        server.join().unwrap();
+        assert!(matches!(
+            result,
+            Err(ProwlError::Backend { status: 400, .. })
+        ));
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::Local;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+use super::{BoxFuture, Notifier, message};
+use crate::api::{ApiResponse, SendRequest};
+use crate::error::{ProwlError, Result};
+
+const DEFAULT_SERVER: &str = "localhost:25";
+
+/// Longest a whole SMTP conversation may take.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Emails notifications through an SMTP relay, without TLS or authentication.
+///
+/// The subject is "[application] event"; high and emergency notifications are marked as
+/// urgent with `X-Priority: 1` and `Importance: high`.
+pub struct SmtpNotifier {
+    server: String,
+    from: String,
+    to: Vec<String>,
+}
+
+impl SmtpNotifier {
+    pub fn new(server: Option<String>, from: String, to: Vec<String>) -> Self {
+        SmtpNotifier {
+            server: server.unwrap_or_else(|| DEFAULT_SERVER.to_string()),
+            from,
+            to,
+        }
+    }
+
+    async fn mail(&self, request: &SendRequest) -> Result<ApiResponse> {
+        if self.to.is_empty() {
+            return Err(ProwlError::Config(
+                "SMTP channel has no \"to\" addresses".to_string(),
+            ));
+        }
+        tokio::time::timeout(TIMEOUT, self.transaction(request))
+            .await
+            .map_err(|_| ProwlError::Delivery(format!("SMTP relay {} timed out", self.server)))?
+    }
+
+    async fn transaction(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let (reader, writer) = TcpStream::connect(&self.server).await?.into_split();
+        let mut session = Session {
+            reader: BufReader::new(reader),
+            writer,
+        };
+
+        session.expect(220).await?;
+        session.command("EHLO localhost", 250).await?;
+        session
+            .command(&format!("MAIL FROM:<{}>", self.from), 250)
+            .await?;
+        for to in &self.to {
+            session.command(&format!("RCPT TO:<{to}>"), 250).await?;
+        }
+        session.command("DATA", 354).await?;
+        session
+            .writer
+            .write_all(self.email(request).as_bytes())
+            .await?;
+        let code = session.expect(250).await?;
+        let _ = session.command("QUIT", 221).await;
+
+        Ok(ApiResponse::success(code.into(), None, None))
+    }
+
+    /// The message for `request`, dot-stuffed and terminated for the DATA command.
+    fn email(&self, request: &SendRequest) -> String {
+        let mut headers = vec![
+            format!("From: {}", self.from),
+            format!("To: {}", self.to.join(", ")),
+            format!(
+                "Subject: [{}] {}",
+                header_safe(&request.application),
+                header_safe(&request.event)
+            ),
+            format!("Date: {}", Local::now().to_rfc2822()),
+            "MIME-Version: 1.0".to_string(),
+            "Content-Type: text/plain; charset=utf-8".to_string(),
+            "Content-Transfer-Encoding: 8bit".to_string(),
+        ];
+        if request.priority >= 1 {
+            headers.push("X-Priority: 1".to_string());
+            headers.push("Importance: high".to_string());
+        }
+
+        let mut body = message(request).to_string();
+        if let Some(ref url) = request.url {
+            body.push_str(&format!("\n\n{url}"));
+        }
+
+        let mut email = headers.join("\r\n");
+        email.push_str("\r\n\r\n");
+        for line in body.lines() {
+            if line.starts_with('.') {
+                email.push('.');
+            }
+            email.push_str(line);
+            email.push_str("\r\n");
+        }
+        email.push_str(".\r\n");
+        email
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn name(&self) -> &str {
+        "smtp"
+    }
+
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+        Box::pin(self.mail(request))
+    }
+}
+
+struct Session {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Session {
+    async fn command(&mut self, command: &str, expected: u16) -> Result<u16> {
+        self.writer
+            .write_all(format!("{command}\r\n").as_bytes())
+            .await?;
+        self.expect(expected).await
+    }
+
+    /// Reads a reply, which may span several "250-..." lines, failing unless its code is
+    /// `expected`.
+    async fn expect(&mut self, expected: u16) -> Result<u16> {
+        let mut reply = String::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line).await? == 0 {
+                return Err(ProwlError::Delivery(
+                    "SMTP relay closed the connection".to_string(),
+                ));
+            }
+            reply.push_str(line.trim_end());
+            if line.as_bytes().get(3) != Some(&b'-') {
+                break;
+            }
+            reply.push(' ');
+        }
+
+        match reply.get(..3).and_then(|code| code.parse().ok()) {
+            Some(code) if code == expected => Ok(code),
+            _ => Err(ProwlError::Delivery(format!("SMTP relay replied: {reply}"))),
+        }
+    }
+}
+
+/// Keeps user text from starting new header lines.
+fn header_safe(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_mail_through_relay() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = listener.local_addr().unwrap().to_string();
+        let relay = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut lines = BufReader::new(stream).lines();
+            let mut transcript = Vec::new();
+            writer.write_all(b"220 relay ready\r\n").unwrap();
+            while let Some(Ok(line)) = lines.next() {
+                let reply: &[u8] = match line.as_str() {
+                    "EHLO localhost" => b"250-relay\r\n250 8BITMIME\r\n",
+                    "DATA" => b"354 go ahead\r\n",
+                    "." => b"250 queued\r\n",
+                    "QUIT" => b"221 bye\r\n",
+                    line if line.starts_with("MAIL") || line.starts_with("RCPT") => b"250 ok\r\n",
+                    _ => b"",
+                };
+                writer.write_all(reply).unwrap();
+                transcript.push(line.clone());
+                if line == "QUIT" {
+                    break;
+                }
+            }
+            transcript
+        });
+
+        let notifier = SmtpNotifier::new(
+            Some(server),
+            "prowl@db1".to_string(),
+            vec!["oncall@example.com".to_string()],
+        );
+        let request = SendRequest {
+            apikey: String::new(),
+            application: "db1".to_string(),
+            event: "Disk full".to_string(),
+            description: "/var at 100%\n.hidden line".to_string(),
+            priority: 2,
+            ..SendRequest::example()
+        };
+        let response = notifier.notify(&request).await.unwrap();
+        assert_eq!(response.code, 250);
+
+        let transcript = relay.join().unwrap();
+        assert!(transcript.contains(&"RCPT TO:<oncall@example.com>".to_string()));
+        assert!(transcript.contains(&"Subject: [db1] Disk full".to_string()));
+        assert!(transcript.contains(&"X-Priority: 1".to_string()));
+        assert!(transcript.contains(&"..hidden line".to_string()));
+    }
+}
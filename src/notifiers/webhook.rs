@@ -0,0 +1,98 @@
+use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+use super::{BoxFuture, Notifier, deliver};
+use crate::api::{ApiResponse, SendRequest};
+use crate::error::{ProwlError, Result};
+
+/// POSTs each notification to a URL as a JSON object:
+///
+/// ```json
+/// {"application": "backup", "event": "Backup failed", "description": "disk full",
+///  "priority": 2, "url": null}
+/// ```
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+    headers: HeaderMap,
+}
+
+impl WebhookNotifier {
+    pub fn new(client: Client, url: String, headers: BTreeMap<String, String>) -> Result<Self> {
+        let headers = headers
+            .into_iter()
+            .map(|(name, value)| {
+                let invalid =
+                    || ProwlError::Config(format!("Invalid webhook header \"{name}: {value}\""));
+                Ok((
+                    HeaderName::try_from(name.as_str()).map_err(|_| invalid())?,
+                    HeaderValue::try_from(value.as_str()).map_err(|_| invalid())?,
+                ))
+            })
+            .collect::<Result<HeaderMap>>()?;
+        Ok(WebhookNotifier {
+            client,
+            url,
+            headers,
+        })
+    }
+
+    async fn post(&self, request: &SendRequest) -> Result<ApiResponse> {
+        let body = json!({
+            "application": request.application,
+            "event": request.event,
+            "description": request.description,
+            "priority": request.priority,
+            "url": request.url,
+        });
+        let builder = self.client.post(&self.url).headers(self.headers.clone());
+        deliver("webhook", super::json(builder, &body)).await
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn notify<'a>(&'a self, request: &'a SendRequest) -> BoxFuture<'a, Result<ApiResponse>> {
+        Box::pin(self.post(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifiers::stand_in;
+
+    #[tokio::test]
+    async fn test_post_json() {
+        let (url, server) = stand_in::serve(204, "");
+        let notifier = WebhookNotifier::new(
+            Client::new(),
+            format!("{url}/hooks/prowl"),
+            BTreeMap::from([("X-Token".to_string(), "s3cret".to_string())]),
+        )
+        .unwrap();
+        let request = SendRequest {
+            apikey: String::new(),
+            application: "backup".to_string(),
+            event: "Backup failed".to_string(),
+            description: "disk full".to_string(),
+            priority: -1,
+            url: None,
+            providerkey: None,
+        };
+        let response = notifier.notify(&request).await.unwrap();
+        assert_eq!(response.code, 204);
+
+        let raw = server.join().unwrap();
+        assert!(raw.starts_with("POST /hooks/prowl "));
+        assert!(raw.to_ascii_lowercase().contains("x-token: s3cret"));
+        let body: serde_json::Value = serde_json::from_str(stand_in::body(&raw)).unwrap();
+        assert_eq!(body["event"], "Backup failed");
+        assert_eq!(body["priority"], -1);
+    }
+}
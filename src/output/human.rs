@@ -1,14 +1,35 @@
+use chrono::{DateTime, Local};
 use colored::Colorize;
 
 use crate::api::{ApiResponse, SendRequest};
+use crate::cli::QrMode;
 use crate::config::Config;
 use crate::error::ProwlError;
-use crate::output::OutputFormatter;
+use crate::heartbeat::HeartbeatStatus;
+use crate::history::{HistoryEntry, SendStatus};
+use crate::output::{
+    BatchOutcome, BatchStatus, KeyOutcome, KeyStatus, LogLevel, OutputFormatter, qr,
+};
+use crate::registry::KeyRegistry;
+use crate::rules::{Action, Verdict};
+use crate::schedule::ScheduledNotification;
+use crate::target::mask_url;
 
 pub struct HumanOutput;
 
 impl OutputFormatter for HumanOutput {
     fn format_send_success(&self, response: &ApiResponse) {
+        if let Some(ref channel) = response.channel {
+            println!(
+                "{} Notification delivered via failover channel {}",
+                "✓".yellow().bold(),
+                channel.yellow()
+            );
+            if let Some(ref error) = response.error_message {
+                println!("  Primary delivery failed: {}", error.red());
+            }
+            return;
+        }
         println!("{} Notification sent successfully", "✓".green().bold());
         if let Some(remaining) = response.remaining {
             println!("  {} API calls remaining", remaining.to_string().cyan());
@@ -25,13 +46,16 @@ impl OutputFormatter for HumanOutput {
         }
     }
 
-    fn format_token_success(&self, response: &ApiResponse) {
+    fn format_token_success(&self, response: &ApiResponse, qr: QrMode) {
         println!("{} Registration token retrieved", "✓".green().bold());
         if let Some(ref token) = response.token {
             println!("\n  Token: {}", token.yellow().bold());
         }
         if let Some(ref url) = response.token_url {
             println!("\n  Approval URL: {}", url.cyan());
+            if let Some(code) = qr::render(url, qr) {
+                println!("\n{code}");
+            }
             println!(
                 "\n  {}",
                 "User must visit the URL above to approve the token.".dimmed()
@@ -55,6 +79,20 @@ impl OutputFormatter for HumanOutput {
         }
     }
 
+    fn format_register_pending(&self, response: &ApiResponse, qr: QrMode) {
+        println!("{} Registration token retrieved", "✓".green().bold());
+        if let Some(ref url) = response.token_url {
+            println!("\n  Approval URL: {}", url.cyan());
+            if let Some(code) = qr::render(url, qr) {
+                println!("\n{code}");
+            }
+            println!(
+                "\n  {}\n",
+                "Waiting for the user to visit the URL above and approve the token...".dimmed()
+            );
+        }
+    }
+
     fn format_error(&self, error: &ProwlError) {
         eprintln!("{} {}", "Error:".red().bold(), error);
     }
@@ -80,14 +118,14 @@ impl OutputFormatter for HumanOutput {
         println!();
 
         if let Some(ref key) = config.api_key {
-            let masked = mask_key(key);
+            let masked = mask_url(key);
             println!("  api_key:      {}", masked.green());
         } else {
             println!("  api_key:      {}", "(not set)".dimmed());
         }
 
         if let Some(ref key) = config.provider_key {
-            let masked = mask_key(key);
+            let masked = mask_url(key);
             println!("  provider_key: {}", masked.green());
         } else {
             println!("  provider_key: {}", "(not set)".dimmed());
@@ -98,6 +136,11 @@ impl OutputFormatter for HumanOutput {
         } else {
             println!("  application:  {}", "(not set)".dimmed());
         }
+
+        if !config.profiles.is_empty() {
+            let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+            println!("  profiles:     {}", names.join(", ").green());
+        }
     }
 
     fn format_config_init(&self, path: &std::path::Path) {
@@ -114,7 +157,7 @@ impl OutputFormatter for HumanOutput {
 
     fn format_config_set(&self, key: &str, value: &str) {
         let display_value = if key.contains("key") {
-            mask_key(value)
+            mask_url(value)
         } else {
             value.to_string()
         };
@@ -125,12 +168,356 @@ impl OutputFormatter for HumanOutput {
             display_value.green()
         );
     }
-}
 
-fn mask_key(key: &str) -> String {
-    if key.len() <= 8 {
-        "*".repeat(key.len())
-    } else {
-        format!("{}...{}", &key[..4], &key[key.len() - 4..])
+    fn format_key_outcomes(&self, action: &str, outcomes: &[KeyOutcome]) {
+        if outcomes.is_empty() {
+            println!("{} No keys to {action}", "●".cyan().bold());
+            return;
+        }
+
+        let label_width = outcomes
+            .iter()
+            .map(|o| o.label.len())
+            .max()
+            .unwrap_or(0)
+            .max("LABEL".len());
+
+        println!(
+            "  {}",
+            format!(
+                "{:<label_width$}  {:<11}  {:<7}  {:>9}  DETAILS",
+                "LABEL", "KEY", "STATUS", "REMAINING"
+            )
+            .dimmed()
+        );
+
+        for outcome in outcomes {
+            let status = format!("{:<7}", outcome.status.as_str());
+            let status = match outcome.status {
+                KeyStatus::Ok => status.green(),
+                KeyStatus::Invalid => status.red(),
+                KeyStatus::Error => status.yellow(),
+                KeyStatus::Held => status.cyan(),
+            };
+            let remaining = outcome
+                .remaining
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let details = match (&outcome.error, &outcome.reset_date) {
+                (Some(error), _) => error.dimmed(),
+                (None, Some(reset)) => format!("resets at {reset}").dimmed(),
+                (None, None) => "".dimmed(),
+            };
+            println!(
+                "  {:<label_width$}  {:<11}  {}  {:>9}  {}",
+                outcome.label,
+                mask_url(&outcome.api_key),
+                status,
+                remaining.cyan(),
+                details
+            );
+        }
+
+        let ok = outcomes.iter().filter(|o| o.status.succeeded()).count();
+        let symbol = if ok == outcomes.len() {
+            "✓".green().bold()
+        } else {
+            "✗".red().bold()
+        };
+        println!("\n{symbol} {action}: {ok} of {} succeeded", outcomes.len());
+    }
+
+    fn format_users_list(&self, registry: &KeyRegistry, path: &std::path::Path) {
+        println!("{} Registered users", "●".cyan().bold());
+        println!("  Path: {}", path.display().to_string().dimmed());
+        println!();
+
+        if registry.users.is_empty() {
+            println!("  {}", "(none)".dimmed());
+            return;
+        }
+
+        let label_width = registry.users.keys().map(String::len).max().unwrap_or(0);
+        for (label, user) in &registry.users {
+            println!(
+                "  {:<label_width$}  {}",
+                label,
+                mask_url(&user.api_key).green()
+            );
+        }
+    }
+
+    fn format_users_update(&self, action: &str, label: &str) {
+        let verb = match action {
+            "add" => "Added",
+            "remove" => "Removed",
+            other => other,
+        };
+        println!("{} {} user {}", "✓".green().bold(), verb, label.cyan());
+    }
+
+    fn format_batch_outcome(&self, outcome: &BatchOutcome) {
+        let line = format!("line {}", outcome.line);
+        let event = outcome.event.as_deref().unwrap_or("");
+        match outcome.status {
+            BatchStatus::Sent => println!("  {} {}  {}", "✓".green(), line.dimmed(), event),
+            BatchStatus::Held => println!(
+                "  {} {}  {} {}",
+                "●".cyan(),
+                line.dimmed(),
+                event,
+                "(held by rule)".dimmed()
+            ),
+            BatchStatus::Valid => println!(
+                "  {} {}  {} {}",
+                "✓".yellow(),
+                line.dimmed(),
+                event,
+                "(dry run)".dimmed()
+            ),
+            BatchStatus::Invalid | BatchStatus::Failed => println!(
+                "  {} {}  {}{}",
+                "✗".red(),
+                line.dimmed(),
+                format!("{}: ", outcome.status.as_str()).red(),
+                outcome.error.as_deref().unwrap_or("")
+            ),
+        }
+    }
+
+    fn format_batch_summary(&self, outcomes: &[BatchOutcome]) {
+        let count = |status| outcomes.iter().filter(|o| o.status == status).count();
+        let (sent, held, valid, invalid, failed) = (
+            count(BatchStatus::Sent),
+            count(BatchStatus::Held),
+            count(BatchStatus::Valid),
+            count(BatchStatus::Invalid),
+            count(BatchStatus::Failed),
+        );
+
+        let symbol = if invalid + failed == 0 {
+            "✓".green().bold()
+        } else {
+            "✗".red().bold()
+        };
+        if valid > 0 {
+            println!("\n{symbol} Batch (dry run): {valid} valid, {invalid} invalid");
+            return;
+        }
+        let held = if held > 0 {
+            format!(", {held} held")
+        } else {
+            String::new()
+        };
+        println!("\n{symbol} Batch: {sent} sent{held}, {invalid} invalid, {failed} failed");
+        if let Some(remaining) = outcomes.iter().rev().find_map(|o| o.remaining) {
+            println!("  {} API calls remaining", remaining.to_string().cyan());
+        }
+    }
+
+    fn format_log(&self, level: LogLevel, message: &str) {
+        let timestamp = humantime::format_rfc3339_seconds(std::time::SystemTime::now());
+        let symbol = match level {
+            LogLevel::Info => "●".cyan(),
+            LogLevel::Success => "✓".green(),
+            LogLevel::Warning => "!".yellow(),
+            LogLevel::Error => "✗".red(),
+        };
+        eprintln!("{} {} {}", timestamp.to_string().dimmed(), symbol, message);
+    }
+
+    fn format_suppressed(&self, dedup_key: &str, repeats: u32, until: std::time::SystemTime) {
+        println!(
+            "{} Notification suppressed as a repeat ({} since it was last sent)",
+            "⊘".yellow().bold(),
+            repeats.to_string().cyan()
+        );
+        println!("  Dedup key: {}", dedup_key.dimmed());
+        println!(
+            "  Throttled until: {}",
+            humantime::format_rfc3339_seconds(until).to_string().cyan()
+        );
+    }
+    fn format_rule_verdict(&self, verdict: &Verdict, at: DateTime<Local>) {
+        let at = at.format("%a %Y-%m-%d %H:%M").to_string();
+        let Some(ref rule) = verdict.rule else {
+            println!(
+                "{} No rule matches at {}; the notification would be sent unchanged",
+                "●".cyan().bold(),
+                at.cyan()
+            );
+            return;
+        };
+        println!(
+            "{} Rule {} matches at {}",
+            "●".cyan().bold(),
+            rule.yellow().bold(),
+            at.cyan()
+        );
+
+        let request = match verdict.action {
+            Action::Drop => {
+                println!("  Action:      {}", "drop".red());
+                return;
+            }
+            Action::Send(ref request) => {
+                println!("  Action:      {}", "send".green());
+                request
+            }
+            Action::Delay { until, ref request } => {
+                println!(
+                    "  Action:      {} until {}",
+                    "delay".yellow(),
+                    until.format("%a %Y-%m-%d %H:%M").to_string().cyan()
+                );
+                request
+            }
+        };
+        println!("  Priority:    {}", request.priority.to_string().cyan());
+        println!(
+            "  API Keys:    {}",
+            format!("{} key(s)", request.apikey.split(',').count()).cyan()
+        );
+    }
+    fn format_scheduled(&self, notification: &ScheduledNotification) {
+        println!(
+            "{} Notification #{} scheduled for {}",
+            "✓".green().bold(),
+            notification.id,
+            notification
+                .due()
+                .format("%a %Y-%m-%d %H:%M")
+                .to_string()
+                .cyan()
+        );
+        println!(
+            "  {}",
+            "It is sent by 'prowl schedule run' once due.".dimmed()
+        );
+    }
+
+    fn format_schedule_list(&self, notifications: &[ScheduledNotification]) {
+        println!("{} Scheduled notifications", "●".cyan().bold());
+        println!();
+
+        if notifications.is_empty() {
+            println!("  {}", "(none)".dimmed());
+            return;
+        }
+
+        let id_width = notifications
+            .iter()
+            .map(|notification| notification.id.to_string().len() + 1)
+            .max()
+            .unwrap_or(0);
+        for notification in notifications {
+            println!(
+                "  {:>id_width$}  {}  {}",
+                format!("#{}", notification.id),
+                notification
+                    .send_at()
+                    .format("%a %Y-%m-%d %H:%M")
+                    .to_string()
+                    .cyan(),
+                notification.request.event
+            );
+        }
+    }
+
+    fn format_schedule_cancel(&self, ids: &[u64]) {
+        for id in ids {
+            println!("{} Cancelled notification #{}", "✓".green().bold(), id);
+        }
+    }
+
+    fn format_heartbeat_ping(&self, name: &str) {
+        println!("{} Pinged heartbeat {}", "✓".green().bold(), name.cyan());
+    }
+
+    fn format_heartbeat_list(&self, heartbeats: &[HeartbeatStatus]) {
+        println!("{} Heartbeats", "●".cyan().bold());
+        println!();
+
+        if heartbeats.is_empty() {
+            println!("  {}", "(none configured)".dimmed());
+            return;
+        }
+
+        let name_width = heartbeats
+            .iter()
+            .map(|heartbeat| heartbeat.name.len())
+            .max()
+            .unwrap_or(0);
+        for heartbeat in heartbeats {
+            let state = if heartbeat.down {
+                "missed".red().bold()
+            } else if heartbeat
+                .deadline
+                .is_some_and(|deadline| deadline < Local::now())
+            {
+                "late".red()
+            } else if heartbeat.last_ping.is_some() {
+                "ok".green()
+            } else {
+                "waiting".yellow()
+            };
+            let last_ping = heartbeat.last_ping.map_or_else(
+                || "never pinged".to_string(),
+                |time| format!("last ping {}", time.format("%a %Y-%m-%d %H:%M")),
+            );
+            let deadline = heartbeat.deadline.map_or_else(String::new, |time| {
+                format!(", due by {}", time.format("%a %Y-%m-%d %H:%M"))
+            });
+            println!(
+                "  {:<name_width$}  {:<7}  every {}, {}{}",
+                heartbeat.name,
+                state,
+                humantime::format_duration(heartbeat.interval),
+                last_ping,
+                deadline.dimmed()
+            );
+        }
+    }
+
+    fn format_history(&self, entries: &[HistoryEntry]) {
+        if entries.is_empty() {
+            println!("{}", "No matching notifications in the history".dimmed());
+            return;
+        }
+
+        for entry in entries {
+            let status = match entry.status {
+                SendStatus::Sent => "sent".green(),
+                SendStatus::Failed => "failed".red().bold(),
+                SendStatus::Dropped => "dropped".yellow(),
+                SendStatus::Delayed => "delayed".cyan(),
+            };
+            println!(
+                "{}  {:<7}  {}  {} {}",
+                entry.time.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+                status,
+                format!("{:+}", entry.priority).cyan(),
+                entry.event.bold(),
+                format!("({})", entry.application).dimmed()
+            );
+
+            let mut details = Vec::new();
+            if !entry.recipients.is_empty() {
+                details.push(format!("to {}", entry.recipients.join(", ")));
+            }
+            if let Some(via) = &entry.via {
+                details.push(format!("via {via}"));
+            }
+            if let Some(code) = entry.code {
+                details.push(format!("code {code}"));
+            }
+            if let Some(remaining) = entry.remaining {
+                details.push(format!("{remaining} remaining"));
+            }
+            println!("    {}", details.join(", ").dimmed());
+            if let Some(error) = &entry.error {
+                println!("    {}", error.red());
+            }
+        }
     }
 }
@@ -1,9 +1,17 @@
+use chrono::{DateTime, Local};
 use serde_json::json;
 
 use crate::api::{ApiResponse, SendRequest};
-use crate::config::Config;
+use crate::cli::QrMode;
+use crate::config::{BackendConfig, Config};
 use crate::error::ProwlError;
-use crate::output::OutputFormatter;
+use crate::heartbeat::HeartbeatStatus;
+use crate::history::HistoryEntry;
+use crate::output::{BatchOutcome, BatchStatus, KeyOutcome, LogLevel, OutputFormatter};
+use crate::registry::KeyRegistry;
+use crate::rules::{Action, Verdict};
+use crate::schedule::ScheduledNotification;
+use crate::target::mask_url;
 
 pub struct JsonOutput;
 
@@ -14,6 +22,8 @@ impl OutputFormatter for JsonOutput {
             "action": "send",
             "remaining": response.remaining,
             "reset_date": response.reset_date,
+            "channel": response.channel,
+            "primary_error": response.channel.as_ref().and(response.error_message.as_ref()),
         });
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
     }
@@ -29,7 +39,7 @@ impl OutputFormatter for JsonOutput {
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
     }
 
-    fn format_token_success(&self, response: &ApiResponse) {
+    fn format_token_success(&self, response: &ApiResponse, _qr: QrMode) {
         let output = json!({
             "success": true,
             "action": "token",
@@ -48,6 +58,16 @@ impl OutputFormatter for JsonOutput {
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
     }
 
+    fn format_register_pending(&self, response: &ApiResponse, _qr: QrMode) {
+        let output = json!({
+            "success": true,
+            "action": "register_pending",
+            "token": response.token,
+            "approval_url": response.token_url,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
     fn format_error(&self, error: &ProwlError) {
         let output = json!({
             "success": false,
@@ -75,9 +95,15 @@ impl OutputFormatter for JsonOutput {
     fn format_config_show(&self, config: &Config, path: &std::path::Path) {
         let output = json!({
             "path": path.display().to_string(),
-            "api_key": config.api_key.as_ref().map(|k| mask_key(k)),
-            "provider_key": config.provider_key.as_ref().map(|k| mask_key(k)),
+            "api_key": config.api_key.as_ref().map(|k| mask_url(k)),
+            "provider_key": config.provider_key.as_ref().map(|k| mask_url(k)),
             "application": config.application,
+            "profiles": config.profiles.iter().map(|(name, profile)| (name.clone(), json!({
+                "api_key": profile.api_key.as_ref().map(|k| mask_url(k)),
+                "provider_key": profile.provider_key.as_ref().map(|k| mask_url(k)),
+                "application": profile.application,
+                "backend": profile.backend.as_ref().map(BackendConfig::name),
+            }))).collect::<serde_json::Map<_, _>>(),
         });
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
     }
@@ -93,7 +119,7 @@ impl OutputFormatter for JsonOutput {
 
     fn format_config_set(&self, key: &str, value: &str) {
         let display_value = if key.contains("key") {
-            mask_key(value)
+            mask_url(value)
         } else {
             value.to_string()
         };
@@ -105,12 +131,184 @@ impl OutputFormatter for JsonOutput {
         });
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
     }
-}
 
-fn mask_key(key: &str) -> String {
-    if key.len() <= 8 {
-        "*".repeat(key.len())
-    } else {
-        format!("{}...{}", &key[..4], &key[key.len() - 4..])
+    fn format_key_outcomes(&self, action: &str, outcomes: &[KeyOutcome]) {
+        let results: Vec<_> = outcomes
+            .iter()
+            .map(|o| {
+                json!({
+                    "label": o.label,
+                    "api_key": mask_url(&o.api_key),
+                    "status": o.status.as_str(),
+                    "remaining": o.remaining,
+                    "reset_date": o.reset_date,
+                    "error": o.error,
+                })
+            })
+            .collect();
+        let ok = outcomes.iter().filter(|o| o.status.succeeded()).count();
+        let output = json!({
+            "success": ok == outcomes.len(),
+            "action": action,
+            "total": outcomes.len(),
+            "ok": ok,
+            "results": results,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    fn format_users_list(&self, registry: &KeyRegistry, path: &std::path::Path) {
+        let users: serde_json::Map<_, _> = registry
+            .users
+            .iter()
+            .map(|(label, user)| (label.clone(), json!(mask_url(&user.api_key))))
+            .collect();
+        let output = json!({
+            "path": path.display().to_string(),
+            "users": users,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    fn format_users_update(&self, action: &str, label: &str) {
+        let output = json!({
+            "success": true,
+            "action": format!("users_{action}"),
+            "label": label,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    // Batch results are streamed as JSON Lines, one object per record as it completes.
+    fn format_batch_outcome(&self, outcome: &BatchOutcome) {
+        let output = json!({
+            "line": outcome.line,
+            "status": outcome.status.as_str(),
+            "event": outcome.event,
+            "remaining": outcome.remaining,
+            "error": outcome.error,
+        });
+        println!("{output}");
+    }
+
+    fn format_batch_summary(&self, outcomes: &[BatchOutcome]) {
+        let count = |status| outcomes.iter().filter(|o| o.status == status).count();
+        let output = json!({
+            "success": outcomes
+                .iter()
+                .all(|o| !matches!(o.status, BatchStatus::Invalid | BatchStatus::Failed)),
+            "action": "batch",
+            "sent": count(BatchStatus::Sent),
+            "held": count(BatchStatus::Held),
+            "valid": count(BatchStatus::Valid),
+            "invalid": count(BatchStatus::Invalid),
+            "failed": count(BatchStatus::Failed),
+        });
+        println!("{output}");
+    }
+
+    fn format_log(&self, level: LogLevel, message: &str) {
+        let output = json!({
+            "timestamp": humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string(),
+            "level": level.as_str(),
+            "message": message,
+        });
+        eprintln!("{output}");
+    }
+
+    fn format_suppressed(&self, dedup_key: &str, repeats: u32, until: std::time::SystemTime) {
+        let output = json!({
+            "success": true,
+            "action": "send",
+            "suppressed": true,
+            "dedup_key": dedup_key,
+            "repeats": repeats,
+            "throttled_until": humantime::format_rfc3339_seconds(until).to_string(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+    fn format_rule_verdict(&self, verdict: &Verdict, at: DateTime<Local>) {
+        let (decision, until, request) = match verdict.action {
+            Action::Send(ref request) => ("send", None, Some(request)),
+            Action::Drop => ("drop", None, None),
+            Action::Delay { until, ref request } => {
+                ("delay", Some(until.to_rfc3339()), Some(request))
+            }
+        };
+        let output = json!({
+            "success": true,
+            "action": "rules_test",
+            "at": at.to_rfc3339(),
+            "rule": verdict.rule,
+            "decision": decision,
+            "until": until,
+            "priority": request.map(|request| request.priority),
+            "recipients": request.map(|request| request.apikey.split(',').count()),
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+    fn format_scheduled(&self, notification: &ScheduledNotification) {
+        let output = json!({
+            "success": true,
+            "action": "schedule",
+            "id": notification.id,
+            "due": notification.due().to_rfc3339(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    fn format_schedule_list(&self, notifications: &[ScheduledNotification]) {
+        let output: Vec<_> = notifications
+            .iter()
+            .map(|notification| {
+                json!({
+                    "id": notification.id,
+                    "due": notification.due().to_rfc3339(),
+                    "held_until": notification.held_until.map(|_| notification.send_at().to_rfc3339()),
+                    "event": notification.request.event,
+                    "description": notification.request.description,
+                    "priority": notification.request.priority,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    fn format_schedule_cancel(&self, ids: &[u64]) {
+        let output = json!({
+            "success": true,
+            "action": "schedule_cancel",
+            "ids": ids,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    fn format_heartbeat_ping(&self, name: &str) {
+        let output = json!({
+            "success": true,
+            "action": "heartbeat_ping",
+            "name": name,
+        });
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    fn format_heartbeat_list(&self, heartbeats: &[HeartbeatStatus]) {
+        let output: Vec<_> = heartbeats
+            .iter()
+            .map(|heartbeat| {
+                json!({
+                    "name": heartbeat.name,
+                    "interval_secs": heartbeat.interval.as_secs(),
+                    "last_ping": heartbeat.last_ping.map(|time| time.to_rfc3339()),
+                    "deadline": heartbeat.deadline.map(|time| time.to_rfc3339()),
+                    "missed": heartbeat.down,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    }
+
+    fn format_history(&self, entries: &[HistoryEntry]) {
+        println!("{}", serde_json::to_string_pretty(entries).unwrap());
     }
 }
@@ -1,22 +1,246 @@
 mod human;
 mod json;
+mod qr;
+mod spinner;
+
+use chrono::{DateTime, Local};
 
 use crate::api::ApiResponse;
-use crate::cli::OutputFormat;
+use crate::cli::{OutputFormat, QrMode};
+use crate::error::{ProwlError, Result};
+use crate::heartbeat::HeartbeatStatus;
+use crate::history::HistoryEntry;
+use crate::registry::KeyRegistry;
+use crate::rules::Verdict;
+use crate::schedule::ScheduledNotification;
 
 pub use human::HumanOutput;
 pub use json::JsonOutput;
+pub use spinner::create_spinner;
 
 pub trait OutputFormatter {
     fn format_send_success(&self, response: &ApiResponse);
     fn format_verify_success(&self, response: &ApiResponse);
-    fn format_token_success(&self, response: &ApiResponse);
+    fn format_token_success(&self, response: &ApiResponse, qr: QrMode);
     fn format_register_success(&self, response: &ApiResponse);
+    fn format_register_pending(&self, response: &ApiResponse, qr: QrMode);
     fn format_error(&self, error: &crate::error::ProwlError);
     fn format_dry_run(&self, request: &crate::api::SendRequest);
     fn format_config_show(&self, config: &crate::config::Config, path: &std::path::Path);
     fn format_config_init(&self, path: &std::path::Path);
     fn format_config_set(&self, key: &str, value: &str);
+    fn format_key_outcomes(&self, action: &str, outcomes: &[KeyOutcome]);
+    fn format_users_list(&self, registry: &KeyRegistry, path: &std::path::Path);
+    fn format_users_update(&self, action: &str, label: &str);
+    fn format_batch_outcome(&self, outcome: &BatchOutcome);
+    fn format_batch_summary(&self, outcomes: &[BatchOutcome]);
+    fn format_log(&self, level: LogLevel, message: &str);
+    fn format_suppressed(&self, dedup_key: &str, repeats: u32, until: std::time::SystemTime);
+    fn format_rule_verdict(&self, verdict: &Verdict, at: DateTime<Local>);
+    fn format_scheduled(&self, notification: &ScheduledNotification);
+    fn format_schedule_list(&self, notifications: &[ScheduledNotification]);
+    fn format_schedule_cancel(&self, ids: &[u64]);
+    fn format_heartbeat_ping(&self, name: &str);
+    fn format_heartbeat_list(&self, heartbeats: &[HeartbeatStatus]);
+    fn format_history(&self, entries: &[HistoryEntry]);
+}
+
+/// Severity of a log line from a long-running mode such as `prowl serve`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Info => "info",
+            LogLevel::Success => "success",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+/// The result of one API call in a bulk operation over many keys.
+#[derive(Debug, Clone)]
+pub struct KeyOutcome {
+    pub label: String,
+    pub api_key: String,
+    pub status: KeyStatus,
+    pub remaining: Option<i32>,
+    pub reset_date: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStatus {
+    Ok,
+    /// The API rejected the key itself (401).
+    Invalid,
+    /// The call failed for some other reason, such as rate limiting or a network error.
+    Error,
+    /// A rule held the notification back; it's in the schedule to send later.
+    Held,
+}
+
+impl KeyStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeyStatus::Ok => "ok",
+            KeyStatus::Invalid => "invalid",
+            KeyStatus::Error => "error",
+            KeyStatus::Held => "held",
+        }
+    }
+
+    /// Whether the key's notification went out or will.
+    pub fn succeeded(self) -> bool {
+        matches!(self, KeyStatus::Ok | KeyStatus::Held)
+    }
+}
+
+impl KeyOutcome {
+    pub fn new(label: &str, api_key: &str, result: &Result<ApiResponse>) -> Self {
+        let (status, remaining, reset_date, error) = match result {
+            Ok(resp) => (KeyStatus::Ok, resp.remaining, resp.reset_date.clone(), None),
+            Err(e @ ProwlError::Api { code: 401, .. }) => {
+                (KeyStatus::Invalid, None, None, Some(e.to_string()))
+            }
+            Err(e) => (KeyStatus::Error, None, None, Some(e.to_string())),
+        };
+
+        KeyOutcome {
+            label: label.to_string(),
+            api_key: api_key.to_string(),
+            status,
+            remaining,
+            reset_date,
+            error,
+        }
+    }
+
+    pub fn held(label: &str, api_key: &str) -> Self {
+        KeyOutcome {
+            label: label.to_string(),
+            api_key: api_key.to_string(),
+            status: KeyStatus::Held,
+            remaining: None,
+            reset_date: None,
+            error: None,
+        }
+    }
+}
+
+/// The result of one record in a batch send.
+#[derive(Debug, Clone)]
+pub struct BatchOutcome {
+    pub line: usize,
+    pub event: Option<String>,
+    pub status: BatchStatus,
+    pub remaining: Option<i32>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchStatus {
+    Sent,
+    /// A rule held the record back; it's in the schedule to send later.
+    Held,
+    /// Dry run: the record is valid and would be sent.
+    Valid,
+    /// The record failed to parse or validate and was never sent.
+    Invalid,
+    Failed,
+}
+
+impl BatchStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BatchStatus::Sent => "sent",
+            BatchStatus::Held => "held",
+            BatchStatus::Valid => "valid",
+            BatchStatus::Invalid => "invalid",
+            BatchStatus::Failed => "failed",
+        }
+    }
+}
+
+impl BatchOutcome {
+    pub fn sent(line: usize, event: &str, response: &ApiResponse) -> Self {
+        BatchOutcome {
+            line,
+            event: Some(event.to_string()),
+            status: BatchStatus::Sent,
+            remaining: response.remaining,
+            error: None,
+        }
+    }
+
+    pub fn held(line: usize, event: &str) -> Self {
+        BatchOutcome {
+            line,
+            event: Some(event.to_string()),
+            status: BatchStatus::Held,
+            remaining: None,
+            error: None,
+        }
+    }
+
+    pub fn valid(line: usize, event: &str) -> Self {
+        BatchOutcome {
+            line,
+            event: Some(event.to_string()),
+            status: BatchStatus::Valid,
+            remaining: None,
+            error: None,
+        }
+    }
+
+    pub fn invalid(line: usize, error: &ProwlError) -> Self {
+        BatchOutcome {
+            line,
+            event: None,
+            status: BatchStatus::Invalid,
+            remaining: None,
+            error: Some(error.to_string()),
+        }
+    }
+
+    pub fn failed(line: usize, event: &str, error: &str) -> Self {
+        BatchOutcome {
+            line,
+            event: Some(event.to_string()),
+            status: BatchStatus::Failed,
+            remaining: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Turns a set of outcomes into the command's result, failing if any key did not succeed.
+pub fn outcomes_result(outcomes: &[KeyOutcome]) -> Result<()> {
+    let failed = outcomes.iter().filter(|o| !o.status.succeeded()).count();
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(ProwlError::PartialFailure {
+            failed,
+            total: outcomes.len(),
+        })
+    }
+}
+
+/// " via <channel>" for a notification a failover channel delivered, for log lines.
+pub fn via_channel(response: &ApiResponse) -> String {
+    response
+        .channel
+        .as_ref()
+        .map(|channel| format!(" via {channel}"))
+        .unwrap_or_default()
 }
 
 pub fn get_formatter(format: OutputFormat) -> Box<dyn OutputFormatter> {
@@ -32,11 +256,26 @@ struct QuietOutput;
 impl OutputFormatter for QuietOutput {
     fn format_send_success(&self, _response: &ApiResponse) {}
     fn format_verify_success(&self, _response: &ApiResponse) {}
-    fn format_token_success(&self, _response: &ApiResponse) {}
+    fn format_token_success(&self, _response: &ApiResponse, _qr: QrMode) {}
     fn format_register_success(&self, _response: &ApiResponse) {}
+    fn format_register_pending(&self, _response: &ApiResponse, _qr: QrMode) {}
     fn format_error(&self, _error: &crate::error::ProwlError) {}
     fn format_dry_run(&self, _request: &crate::api::SendRequest) {}
     fn format_config_show(&self, _config: &crate::config::Config, _path: &std::path::Path) {}
     fn format_config_init(&self, _path: &std::path::Path) {}
     fn format_config_set(&self, _key: &str, _value: &str) {}
+    fn format_key_outcomes(&self, _action: &str, _outcomes: &[KeyOutcome]) {}
+    fn format_users_list(&self, _registry: &KeyRegistry, _path: &std::path::Path) {}
+    fn format_users_update(&self, _action: &str, _label: &str) {}
+    fn format_batch_outcome(&self, _outcome: &BatchOutcome) {}
+    fn format_batch_summary(&self, _outcomes: &[BatchOutcome]) {}
+    fn format_log(&self, _level: LogLevel, _message: &str) {}
+    fn format_suppressed(&self, _dedup_key: &str, _repeats: u32, _until: std::time::SystemTime) {}
+    fn format_rule_verdict(&self, _verdict: &Verdict, _at: DateTime<Local>) {}
+    fn format_scheduled(&self, _notification: &ScheduledNotification) {}
+    fn format_schedule_list(&self, _notifications: &[ScheduledNotification]) {}
+    fn format_schedule_cancel(&self, _ids: &[u64]) {}
+    fn format_heartbeat_ping(&self, _name: &str) {}
+    fn format_heartbeat_list(&self, _heartbeats: &[HeartbeatStatus]) {}
+    fn format_history(&self, _entries: &[HistoryEntry]) {}
 }
@@ -0,0 +1,68 @@
+use colored::Colorize;
+use console::Term;
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+
+use crate::cli::QrMode;
+
+/// Modules of blank border on each side, as required by the QR spec for reliable scanning.
+const QUIET_ZONE: usize = 4;
+
+/// Renders `url` as a QR code made of Unicode half blocks, or returns `None` when `mode`
+/// and the terminal don't allow it.
+///
+/// The code is drawn black-on-white explicitly so it scans regardless of the terminal's
+/// theme, which means it needs colors; with colors disabled the caller falls back to
+/// printing the URL as text.
+pub fn render(url: &str, mode: QrMode) -> Option<String> {
+    if matches!(mode, QrMode::Never) || !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return None;
+    }
+
+    let code = QrCode::new(url.as_bytes()).ok()?;
+    let width = code.width() + 2 * QUIET_ZONE;
+
+    if matches!(mode, QrMode::Auto) {
+        let term = Term::stdout();
+        match term.size_checked() {
+            Some((_, columns)) if term.is_term() && usize::from(columns) >= width + 2 => {}
+            _ => return None,
+        }
+    }
+
+    let lines: Vec<String> = draw(&code)
+        .lines()
+        .map(|line| format!("  {}", line.black().on_white()))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// The code with its quiet zone, two rows of modules per line of text and dark modules as
+/// block characters.
+fn draw(code: &QrCode) -> String {
+    code.render::<Dense1x2>()
+        .dark_color(Dense1x2::Dark)
+        .light_color(Dense1x2::Light)
+        .quiet_zone(true)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw() {
+        let code = QrCode::new(b"https://www.prowlapp.com/retrieve.php?token=abc").unwrap();
+        let width = code.width() + 2 * QUIET_ZONE;
+        let image = draw(&code);
+        let lines: Vec<Vec<char>> = image.lines().map(|line| line.chars().collect()).collect();
+
+        assert_eq!(lines.len(), width.div_ceil(2));
+        assert!(lines.iter().all(|line| line.len() == width));
+        // The quiet zone is light, and the finder pattern's corner in from it is dark.
+        assert!(lines[0].iter().all(|c| *c == ' '));
+        assert!(lines.iter().all(|line| line[0] == ' '));
+        assert_eq!(lines[QUIET_ZONE / 2][QUIET_ZONE], '█');
+    }
+}
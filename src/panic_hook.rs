@@ -0,0 +1,204 @@
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic::PanicHookInfo;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::adapters::truncate;
+use crate::api::{Priority, ProwlClient, SendRequest};
+use crate::error::Result;
+
+/// How long a panicking process waits for the notification to go out, unless configured.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Sends an emergency notification when the process panics.
+///
+/// ```no_run
+/// prowl::panic_hook::PanicNotifier::new("API_KEY")?
+///     .with_application("billing")
+///     .install();
+/// # Ok::<(), prowl::error::ProwlError>(())
+/// ```
+///
+/// The hook sends synchronously, waiting at most the timeout, then calls the hook that was
+/// installed before it, so the usual panic message is still printed.
+#[derive(Clone)]
+pub struct PanicNotifier {
+    client: ProwlClient,
+    template: SendRequest,
+    timeout: Duration,
+    backtrace: bool,
+}
+
+impl PanicNotifier {
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        Ok(PanicNotifier {
+            client: ProwlClient::new()?,
+            template: SendRequest {
+                apikey: api_key.into(),
+                application: default_application(),
+                event: String::new(),
+                description: String::new(),
+                priority: Priority::Emergency.as_i8(),
+                url: None,
+                providerkey: None,
+            },
+            timeout: DEFAULT_TIMEOUT,
+            backtrace: true,
+        })
+    }
+
+    /// Application name shown in notifications [default: the executable's name].
+    pub fn with_application(mut self, application: impl Into<String>) -> Self {
+        self.template.application = application.into();
+        self
+    }
+
+    pub fn with_provider_key(mut self, provider_key: impl Into<String>) -> Self {
+        self.template.providerkey = Some(provider_key.into());
+        self
+    }
+
+    /// Longest the panicking thread waits for the send [default: 5s].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Whether to include a backtrace, captured even without `RUST_BACKTRACE` [default: true].
+    pub fn with_backtrace(mut self, backtrace: bool) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+
+    /// Sends through `client`, e.g. one with rules or history attached.
+    pub fn with_client(mut self, client: ProwlClient) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Installs the hook, chaining to the one installed before it.
+    pub fn install(self) {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = self.notify(info);
+            previous(info);
+        }));
+    }
+
+    /// The notification for the panic described by `info`.
+    pub fn request(&self, info: &PanicHookInfo<'_>) -> SendRequest {
+        let thread = std::thread::current();
+        let backtrace = self.backtrace.then(Backtrace::force_capture);
+        let mut description = describe(
+            thread.name().unwrap_or("<unnamed>"),
+            info.location()
+                .map(|location| location.to_string())
+                .as_deref(),
+            &payload_message(info.payload()),
+            backtrace.as_ref(),
+        );
+        truncate(&mut description, SendRequest::MAX_DESCRIPTION_LEN);
+
+        let mut event = format!("{} panicked", self.template.application);
+        truncate(&mut event, SendRequest::MAX_EVENT_LEN);
+
+        SendRequest {
+            event,
+            description,
+            ..self.template.clone()
+        }
+    }
+
+    /// Sends the notification for `info`, waiting at most the timeout. The send runs on its
+    /// own thread and runtime, so this works from inside an async runtime too.
+    pub fn notify(&self, info: &PanicHookInfo<'_>) -> Result<()> {
+        let request = self.request(info);
+        let client = self.client.clone();
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::Builder::new()
+            .name("prowl-panic".to_string())
+            .spawn(move || {
+                let result = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(Into::into)
+                    .and_then(|runtime| runtime.block_on(client.send(&request)));
+                let _ = sender.send(result.map(|_| ()));
+            })?;
+
+        receiver.recv_timeout(self.timeout).unwrap_or_else(|_| {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "timed out sending the panic notification",
+            )
+            .into())
+        })
+    }
+}
+
+/// Installs a panic hook sending to `api_key` with the default settings.
+pub fn install(api_key: impl Into<String>) -> Result<()> {
+    PanicNotifier::new(api_key)?.install();
+    Ok(())
+}
+
+fn describe(
+    thread: &str,
+    location: Option<&str>,
+    message: &str,
+    backtrace: Option<&Backtrace>,
+) -> String {
+    let mut description = match location {
+        Some(location) => format!("thread '{thread}' panicked at {location}:\n{message}"),
+        None => format!("thread '{thread}' panicked:\n{message}"),
+    };
+    if let Some(backtrace) = backtrace
+        && backtrace.status() == BacktraceStatus::Captured
+    {
+        description.push_str(&format!("\n\nstack backtrace:\n{backtrace}"));
+    }
+    description
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// The running executable's name, for notifications from library users.
+pub(crate) fn default_application() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.file_stem()?.to_str().map(str::to_string))
+        .unwrap_or_else(|| "prowl".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_panic() {
+        let payload: Box<dyn Any + Send> = Box::new(String::from("index out of bounds"));
+        let description = describe(
+            "worker-3",
+            Some("src/queue.rs:42:9"),
+            &payload_message(payload.as_ref()),
+            None,
+        );
+        assert_eq!(
+            description,
+            "thread 'worker-3' panicked at src/queue.rs:42:9:\nindex out of bounds"
+        );
+
+        let backtrace = Backtrace::force_capture();
+        assert!(describe("main", None, "boom", Some(&backtrace)).contains("stack backtrace:"));
+    }
+}
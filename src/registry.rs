@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::error::{ProwlError, Result};
+use crate::store::{LockedStore, data_path};
+
+/// Provider-side store of user API keys collected through `prowl register`, keyed by label.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyRegistry {
+    #[serde(default)]
+    pub users: BTreeMap<String, RegisteredUser>,
+}
+
+pub type RegistryFile = LockedStore<KeyRegistry>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredUser {
+    pub api_key: String,
+}
+
+impl KeyRegistry {
+    /// Opens and locks the registry for changes.
+    pub fn open() -> Result<RegistryFile> {
+        LockedStore::open_private("users.toml", "key registry")
+    }
+
+    /// Reads the registry without creating it when there is none yet.
+    pub fn load() -> Result<Self> {
+        if !Self::path()?.exists() {
+            return Ok(KeyRegistry::default());
+        }
+        Ok(Self::open()?.data)
+    }
+
+    pub fn path() -> Result<PathBuf> {
+        data_path("users.toml")
+    }
+
+    pub fn insert(&mut self, label: &str, api_key: &str) {
+        self.users.insert(
+            label.to_string(),
+            RegisteredUser {
+                api_key: api_key.to_string(),
+            },
+        );
+    }
+
+    /// Returns the entries for `labels`, or every entry when `labels` is empty.
+    pub fn select<'a>(&'a self, labels: &[String]) -> Result<Vec<(&'a str, &'a str)>> {
+        if labels.is_empty() {
+            return Ok(self
+                .users
+                .iter()
+                .map(|(label, user)| (label.as_str(), user.api_key.as_str()))
+                .collect());
+        }
+
+        labels
+            .iter()
+            .map(|label| {
+                self.users
+                    .get_key_value(label)
+                    .map(|(label, user)| (label.as_str(), user.api_key.as_str()))
+                    .ok_or_else(|| ProwlError::Config(format!("Unknown user: {label}")))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,316 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use regex::Regex;
+
+use crate::api::{Priority, SendRequest};
+use crate::config::{Config, RuleAction, RuleConfig};
+use crate::error::{ProwlError, Result};
+use crate::target::recipient_keys;
+
+/// The `[[rules]]` from the config file, validated and ready to evaluate.
+#[derive(Debug, Clone, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    name: String,
+    hours: Option<(NaiveTime, NaiveTime)>,
+    days: Vec<Weekday>,
+    event: Option<Regex>,
+    application: Option<String>,
+    min_priority: Option<i8>,
+    max_priority: Option<i8>,
+    action: RuleAction,
+    set_priority: Option<Priority>,
+    to: Vec<String>,
+}
+
+/// What the rules decided for one notification.
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    /// Name of the rule that matched, if any.
+    pub rule: Option<String>,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    Send(SendRequest),
+    Drop,
+    Delay {
+        until: DateTime<Local>,
+        request: SendRequest,
+    },
+}
+
+impl Rules {
+    pub fn compile(configs: &[RuleConfig]) -> Result<Self> {
+        let rules = configs
+            .iter()
+            .map(|config| {
+                Rule::compile(config).map_err(|message| {
+                    ProwlError::Config(format!("Rule \"{}\": {message}", config.name))
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Rules { rules })
+    }
+
+    /// Compiles the rules from the config file.
+    pub fn load() -> Result<Self> {
+        Self::compile(&Config::load()?.rules)
+    }
+
+    /// Applies the first rule that matches `request` at `now`.
+    pub fn evaluate(&self, request: &SendRequest, now: DateTime<Local>) -> Verdict {
+        let Some(rule) = self.rules.iter().find(|rule| rule.matches(request, now)) else {
+            return Verdict {
+                rule: None,
+                action: Action::Send(request.clone()),
+            };
+        };
+
+        let mut request = request.clone();
+        if let Some(priority) = rule.set_priority {
+            request.priority = priority.as_i8();
+        }
+        if !rule.to.is_empty() {
+            request.apikey = rule.to.join(",");
+        }
+
+        let action = match rule.action {
+            RuleAction::Send => Action::Send(request),
+            RuleAction::Drop => Action::Drop,
+            RuleAction::Delay => Action::Delay {
+                until: rule.window_end(now),
+                request,
+            },
+        };
+        Verdict {
+            rule: Some(rule.name.clone()),
+            action,
+        }
+    }
+}
+
+impl Rule {
+    fn compile(config: &RuleConfig) -> std::result::Result<Self, String> {
+        let hours = config.hours.as_deref().map(parse_hours).transpose()?;
+        if config.action == RuleAction::Delay && hours.is_none() {
+            return Err("action \"delay\" needs an hours window to wait for".to_string());
+        }
+
+        let days = config
+            .days
+            .iter()
+            .map(|day| {
+                day.parse::<Weekday>()
+                    .map_err(|_| format!("invalid day \"{day}\""))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+
+        let event = config
+            .event
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| format!("invalid event pattern: {e}"))?;
+
+        Ok(Rule {
+            name: config.name.clone(),
+            hours,
+            days,
+            event,
+            application: config.application.clone(),
+            min_priority: config.min_priority.map(Priority::as_i8),
+            max_priority: config.max_priority.map(Priority::as_i8),
+            action: config.action,
+            set_priority: config.set_priority,
+            to: recipient_keys(&config.to).map_err(|e| e.to_string())?,
+        })
+    }
+
+    fn matches(&self, request: &SendRequest, now: DateTime<Local>) -> bool {
+        let time = now.time();
+        let mut day = now.weekday();
+
+        if let Some((start, end)) = self.hours {
+            let inside = if start <= end {
+                start <= time && time < end
+            } else {
+                // Past midnight, the early hours belong to the window that opened yesterday.
+                if time < end {
+                    day = day.pred();
+                }
+                time >= start || time < end
+            };
+            if !inside {
+                return false;
+            }
+        }
+
+        (self.days.is_empty() || self.days.contains(&day))
+            && self
+                .event
+                .as_ref()
+                .is_none_or(|event| event.is_match(&request.event))
+            && self
+                .application
+                .as_ref()
+                .is_none_or(|application| *application == request.application)
+            && self.min_priority.is_none_or(|min| request.priority >= min)
+            && self.max_priority.is_none_or(|max| request.priority <= max)
+    }
+
+    /// The next time the rule's window closes after `now`.
+    fn window_end(&self, now: DateTime<Local>) -> DateTime<Local> {
+        let Some((_, end)) = self.hours else {
+            return now;
+        };
+        let mut date = now.date_naive();
+        if now.time() >= end {
+            date = date.succ_opt().unwrap_or(date);
+        }
+        Local
+            .from_local_datetime(&date.and_time(end))
+            .earliest()
+            // The end time falls in a DST gap that day; an hour later exists.
+            .unwrap_or_else(|| now + Duration::hours(1))
+    }
+}
+
+/// Parses a local date and time such as "2026-10-19T09:00" or "2026-10-19 09:00:30", or an
+/// RFC 3339 timestamp with an offset.
+pub fn parse_local_time(value: &str) -> std::result::Result<DateTime<Local>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Local));
+    }
+    [
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%d %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+    .and_then(|time| Local.from_local_datetime(&time).earliest())
+    .ok_or_else(|| "expected a local time such as \"2026-10-19T09:00\"".to_string())
+}
+
+/// Converts a Unix timestamp to local time, falling back to now if it's out of range.
+pub(crate) fn local_time(timestamp: i64) -> DateTime<Local> {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .earliest()
+        .unwrap_or_else(Local::now)
+}
+
+fn parse_hours(hours: &str) -> std::result::Result<(NaiveTime, NaiveTime), String> {
+    let invalid = || format!("invalid hours \"{hours}\" (expected e.g. \"22:00-07:00\")");
+    let (start, end) = hours.split_once('-').ok_or_else(invalid)?;
+    let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| invalid());
+    Ok((parse(start)?, parse(end)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn request(event: &str, priority: i8) -> SendRequest {
+        SendRequest {
+            event: event.to_string(),
+            priority,
+            ..SendRequest::example()
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        // 2026-10-16 is a Friday.
+        let naive = NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap();
+        Local.from_local_datetime(&naive).earliest().unwrap()
+    }
+
+    fn rules() -> Rules {
+        Rules::compile(&[
+            RuleConfig {
+                name: "pages-always".to_string(),
+                min_priority: Some(Priority::Emergency),
+                ..RuleConfig::default()
+            },
+            RuleConfig {
+                name: "weekend-nights".to_string(),
+                hours: Some("22:00-07:00".to_string()),
+                days: vec!["fri".to_string(), "sat".to_string()],
+                action: RuleAction::Delay,
+                ..RuleConfig::default()
+            },
+            RuleConfig {
+                name: "backups".to_string(),
+                event: Some("^backup".to_string()),
+                set_priority: Some(Priority::VeryLow),
+                to: vec!["ops".to_string()],
+                ..RuleConfig::default()
+            },
+            RuleConfig {
+                name: "noise".to_string(),
+                max_priority: Some(Priority::VeryLow),
+                action: RuleAction::Drop,
+                ..RuleConfig::default()
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = rules();
+
+        let verdict = rules.evaluate(&request("db down", 2), at(16, 23, 0));
+        assert_eq!(verdict.rule.as_deref(), Some("pages-always"));
+
+        // Saturday 03:00 is inside Friday night's window.
+        let verdict = rules.evaluate(&request("db slow", 0), at(17, 3, 0));
+        assert_eq!(verdict.rule.as_deref(), Some("weekend-nights"));
+        let Action::Delay { until, .. } = verdict.action else {
+            panic!("expected a delay");
+        };
+        assert_eq!(until, at(17, 7, 0));
+
+        // Sunday 23:00 opens a window on a day the rule doesn't cover.
+        let verdict = rules.evaluate(&request("backup done", 0), at(18, 23, 0));
+        assert_eq!(verdict.rule.as_deref(), Some("backups"));
+        let Action::Send(request) = verdict.action else {
+            panic!("expected a send");
+        };
+        assert_eq!(request.priority, -2);
+        assert_eq!(request.apikey, "ops");
+
+        let verdict = rules.evaluate(&self::request("chatter", -2), at(19, 12, 0));
+        assert!(matches!(verdict.action, Action::Drop));
+
+        let verdict = rules.evaluate(&self::request("chatter", 0), at(19, 12, 0));
+        assert_eq!(verdict.rule, None);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let delay_without_hours = RuleConfig {
+            name: "bad".to_string(),
+            action: RuleAction::Delay,
+            ..RuleConfig::default()
+        };
+        assert!(Rules::compile(&[delay_without_hours]).is_err());
+
+        let bad_hours = RuleConfig {
+            name: "bad".to_string(),
+            hours: Some("late".to_string()),
+            ..RuleConfig::default()
+        };
+        assert!(Rules::compile(&[bad_hours]).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::api::SendRequest;
+use crate::error::Result;
+use crate::rules::local_time;
+use crate::store::LockedStore;
+
+/// How long a notification one `prowl schedule run` has claimed is left alone by others,
+/// in case that run died before it could send it (seconds).
+const CLAIM_TIMEOUT: i64 = 10 * 60;
+
+/// Notifications waiting for `prowl schedule run` to send them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleStore {
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    pub notifications: Vec<ScheduledNotification>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledNotification {
+    pub id: u64,
+    /// When to send (Unix seconds).
+    pub due: i64,
+    /// When a `delay` rule held it back to, if one did; `due` keeps the original time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub held_until: Option<i64>,
+    /// When a `prowl schedule run` took it to send.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed: Option<i64>,
+    pub request: SendRequest,
+}
+
+/// The schedule store file, locked while in use.
+pub type ScheduleFile = LockedStore<ScheduleStore>;
+
+impl ScheduledNotification {
+    pub fn due(&self) -> DateTime<Local> {
+        local_time(self.due)
+    }
+
+    /// When it will be sent: when it's due, or when the rule holding it lets it go.
+    pub fn send_at(&self) -> DateTime<Local> {
+        local_time(self.send_at_timestamp())
+    }
+
+    fn send_at_timestamp(&self) -> i64 {
+        self.held_until.unwrap_or(self.due)
+    }
+}
+
+impl ScheduleStore {
+    pub fn open() -> Result<ScheduleFile> {
+        LockedStore::open("schedule.toml", "schedule")
+    }
+
+    pub fn add(&mut self, due: DateTime<Local>, request: SendRequest) -> ScheduledNotification {
+        self.next_id += 1;
+        let notification = ScheduledNotification {
+            id: self.next_id,
+            due: due.timestamp(),
+            held_until: None,
+            claimed: None,
+            request,
+        };
+        self.notifications.push(notification.clone());
+        self.sort();
+        notification
+    }
+
+    /// Removes the notification with `id`, returning false if there is none.
+    pub fn cancel(&mut self, id: u64) -> bool {
+        let before = self.notifications.len();
+        self.notifications
+            .retain(|notification| notification.id != id);
+        self.notifications.len() != before
+    }
+
+    /// Claims the notifications to send at or before `now`, earliest first, so that other
+    /// runs leave them alone while they're sent. Each should then be cancelled once sent,
+    /// or held or released to try again later.
+    pub fn claim_due(&mut self, now: DateTime<Local>) -> Vec<ScheduledNotification> {
+        let now = now.timestamp();
+        self.notifications
+            .iter_mut()
+            .filter(|notification| {
+                notification.send_at_timestamp() <= now
+                    && notification
+                        .claimed
+                        .is_none_or(|claimed| now - claimed >= CLAIM_TIMEOUT)
+            })
+            .map(|notification| {
+                notification.claimed = Some(now);
+                notification.clone()
+            })
+            .collect()
+    }
+
+    /// Holds the notification with `id` back until `until`, keeping its original due time.
+    pub fn hold(&mut self, id: u64, until: DateTime<Local>) {
+        if let Some(notification) = self.find(id) {
+            notification.held_until = Some(until.timestamp());
+            notification.claimed = None;
+        }
+        self.sort();
+    }
+
+    /// Gives up the claim on the notification with `id`, for the next run to try again.
+    pub fn release(&mut self, id: u64) {
+        if let Some(notification) = self.find(id) {
+            notification.claimed = None;
+        }
+    }
+
+    fn find(&mut self, id: u64) -> Option<&mut ScheduledNotification> {
+        self.notifications
+            .iter_mut()
+            .find(|notification| notification.id == id)
+    }
+
+    fn sort(&mut self) {
+        self.notifications
+            .sort_by_key(ScheduledNotification::send_at_timestamp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn request(event: &str) -> SendRequest {
+        SendRequest {
+            event: event.to_string(),
+            ..SendRequest::example()
+        }
+    }
+
+    fn events(notifications: Vec<ScheduledNotification>) -> Vec<String> {
+        notifications
+            .into_iter()
+            .map(|notification| notification.request.event)
+            .collect()
+    }
+
+    #[test]
+    fn test_due_and_cancel() {
+        let now = Local::now();
+        let mut store = ScheduleStore::default();
+        let later = store.add(now + Duration::hours(2), request("later"));
+        let soon = store.add(now - Duration::minutes(5), request("soon"));
+        let missed = store.add(now - Duration::hours(1), request("missed"));
+
+        assert_eq!(events(store.claim_due(now)), ["missed", "soon"]);
+        // Claimed ones are left to the run that claimed them until it gives them back.
+        assert!(store.claim_due(now).is_empty());
+        assert_eq!(
+            events(store.claim_due(now + Duration::seconds(CLAIM_TIMEOUT))),
+            ["missed", "soon"]
+        );
+        store.release(missed.id);
+        assert_eq!(events(store.claim_due(now)), ["missed"]);
+
+        // A held notification keeps its due time but waits for the hold.
+        store.hold(soon.id, now + Duration::hours(3));
+        assert_eq!(store.notifications.last().unwrap().due, soon.due);
+        assert_eq!(
+            events(store.claim_due(now + Duration::hours(2) + Duration::seconds(CLAIM_TIMEOUT))),
+            ["missed", "later"]
+        );
+
+        assert!(store.cancel(soon.id));
+        assert!(!store.cancel(soon.id));
+        assert_eq!(store.notifications.len(), 2);
+        assert_ne!(later.id, soon.id);
+
+        // Ids aren't reused after a round trip through the file format.
+        let mut reloaded: ScheduleStore =
+            toml::from_str(&toml::to_string_pretty(&store).unwrap()).unwrap();
+        assert_eq!(reloaded.add(now, request("next")).id, 4);
+    }
+}
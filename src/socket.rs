@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use crate::api::{ApiResponse, PriorityValue, SendRequest};
+use crate::config::Config;
+use crate::delivery::Sent;
+use crate::error::{ProwlError, Result};
+use crate::schedule::ScheduledNotification;
+
+/// Socket used when neither `--socket`, `PROWL_SOCKET` nor `[daemon] socket` is set.
+pub const DEFAULT_SOCKET: &str = "/run/prowl/prowl.sock";
+
+/// Largest request line the daemon reads.
+pub const MAX_LINE_LEN: u64 = 64 * 1024;
+
+/// One line from a `prowl send --via-socket` client to `prowl daemon`, which answers each
+/// with one [`SocketResponse`] line.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SocketRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<PriorityValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application: Option<String>,
+    /// Additional API keys to send to, besides the daemon's own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SocketResponse {
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Schedule ID, when a `delay` rule held the notification back rather than it being sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled: Option<u64>,
+    /// When the held notification is due (Unix seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduled_for: Option<i64>,
+}
+
+impl SocketResponse {
+    pub fn from_result(result: &Result<ApiResponse>) -> Self {
+        match result {
+            Ok(response) => SocketResponse {
+                success: true,
+                code: Some(response.code),
+                remaining: response.remaining,
+                reset_date: response.reset_date.clone(),
+                ..SocketResponse::default()
+            },
+            Err(e) => SocketResponse::error(e),
+        }
+    }
+
+    /// Accepted, but held in the daemon's schedule for `prowl schedule run` to send.
+    pub fn held(notification: &ScheduledNotification) -> Self {
+        SocketResponse {
+            success: true,
+            scheduled: Some(notification.id),
+            scheduled_for: Some(notification.due),
+            ..SocketResponse::default()
+        }
+    }
+
+    pub fn error(error: &ProwlError) -> Self {
+        let code = match error {
+            ProwlError::Api { code, .. } => Some(*code),
+            _ => None,
+        };
+        let error = match error {
+            ProwlError::Api { message, .. } | ProwlError::Daemon(message) => message.clone(),
+            other => other.to_string(),
+        };
+        SocketResponse {
+            success: false,
+            code,
+            error: Some(error),
+            ..SocketResponse::default()
+        }
+    }
+}
+
+/// The socket path: `flag` (from `--socket` or `PROWL_SOCKET`), then `[daemon] socket`.
+pub fn socket_path(flag: Option<&Path>, config: &Config) -> PathBuf {
+    flag.map(Path::to_path_buf)
+        .or_else(|| config.daemon.as_ref()?.socket.clone())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_SOCKET))
+}
+
+/// Submits one notification to the daemon at `path` and waits for the API's answer, or
+/// for word that a rule held it in the daemon's schedule.
+pub async fn submit(path: &Path, request: &SocketRequest) -> Result<Sent> {
+    let stream = UnixStream::connect(path)
+        .await
+        .map_err(|e| ProwlError::Daemon(format!("Could not connect to {}: {e}", path.display())))?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut line = serde_json::to_string(request)
+        .map_err(|e| ProwlError::Daemon(format!("Could not encode request: {e}")))?;
+    line.push('\n');
+    // A daemon that refuses the connection replies and hangs up without reading, so a
+    // failed write still leaves its answer to read.
+    let written = match writer.write_all(line.as_bytes()).await {
+        Ok(()) => writer.shutdown().await,
+        Err(e) => Err(e),
+    };
+
+    let mut reply = String::new();
+    BufReader::new(reader).read_line(&mut reply).await?;
+    if reply.is_empty() {
+        written?;
+        return Err(ProwlError::Daemon(
+            "Connection closed without a reply".to_string(),
+        ));
+    }
+    let reply: SocketResponse = serde_json::from_str(&reply)
+        .map_err(|e| ProwlError::Daemon(format!("Malformed reply: {e}")))?;
+
+    if let (true, Some(id)) = (reply.success, reply.scheduled) {
+        return Ok(Sent::Held(ScheduledNotification {
+            id,
+            due: reply.scheduled_for.unwrap_or_default(),
+            held_until: None,
+            claimed: None,
+            // The daemon keeps its API key to itself; this is only for showing.
+            request: SendRequest {
+                apikey: String::new(),
+                application: request.application.clone().unwrap_or_default(),
+                event: request.event.clone().unwrap_or_default(),
+                description: request.description.clone(),
+                priority: match &request.priority {
+                    Some(priority) => priority.as_i8()?,
+                    None => 0,
+                },
+                url: request.url.clone(),
+                providerkey: None,
+            },
+        }));
+    }
+    match (reply.success, reply.code) {
+        (true, code) => Ok(Sent::Delivered(ApiResponse::success(
+            code.unwrap_or(200),
+            reply.remaining,
+            reply.reset_date,
+        ))),
+        (false, Some(code)) => Err(ProwlError::from_api_code(code, reply.error)),
+        (false, None) => Err(ProwlError::Daemon(
+            reply.error.unwrap_or_else(|| "Request failed".to_string()),
+        )),
+    }
+}
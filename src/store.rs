@@ -0,0 +1,76 @@
+use directories::ProjectDirs;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::path::PathBuf;
+
+use crate::error::{ProwlError, Result};
+
+/// A TOML state file in the data directory, locked for as long as this value is alive so
+/// concurrent invocations take turns.
+pub struct LockedStore<T> {
+    file: File,
+    name: &'static str,
+    pub data: T,
+}
+
+impl<T: Default + Serialize + DeserializeOwned> LockedStore<T> {
+    /// Opens and locks `file_name`, waiting for any other holder; a missing file is empty.
+    /// `name` describes the store in error messages.
+    pub fn open(file_name: &str, name: &'static str) -> Result<Self> {
+        Self::open_with(file_name, name, File::options())
+    }
+
+    /// Like [`open`](Self::open), but a new file is readable only by its owner, for stores
+    /// that hold secrets.
+    pub fn open_private(file_name: &str, name: &'static str) -> Result<Self> {
+        let mut options = File::options();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        Self::open_with(file_name, name, options)
+    }
+
+    fn open_with(file_name: &str, name: &'static str, mut options: OpenOptions) -> Result<Self> {
+        let path = data_path(file_name)?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = options
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        file.lock()?;
+
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+        let data = toml::from_str(&contents)
+            .map_err(|e| ProwlError::Config(format!("Failed to parse {name}: {e}")))?;
+
+        Ok(LockedStore { file, name, data })
+    }
+
+    /// Writes the data back and releases the lock.
+    pub fn save(mut self) -> Result<()> {
+        let contents = toml::to_string_pretty(&self.data)
+            .map_err(|e| ProwlError::Config(format!("Failed to serialize {}: {e}", self.name)))?;
+
+        self.file.set_len(0)?;
+        self.file.rewind()?;
+        self.file.write_all(contents.as_bytes())?;
+        Ok(())
+    }
+}
+
+pub fn data_path(file_name: &str) -> Result<PathBuf> {
+    ProjectDirs::from("", "", "prowl")
+        .map(|dirs| dirs.data_dir().join(file_name))
+        .ok_or_else(|| ProwlError::Config("Could not determine data directory".to_string()))
+}
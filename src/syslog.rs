@@ -0,0 +1,278 @@
+use crate::api::Priority;
+
+/// Facility names accepted by `--facility`, indexed by facility code.
+const FACILITIES: &[&str] = &[
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+const MONTHS: &[&str] = &[
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// A syslog message in either RFC 3164 (BSD) or RFC 5424 format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyslogMessage {
+    pub facility: u8,
+    pub severity: u8,
+    pub hostname: Option<String>,
+    pub program: Option<String>,
+    pub message: String,
+}
+
+impl SyslogMessage {
+    /// Maps the syslog severity onto a notification priority.
+    pub fn priority(&self) -> Priority {
+        match self.severity {
+            0 | 1 => Priority::Emergency,
+            2 | 3 => Priority::High,
+            4 => Priority::Normal,
+            5 | 6 => Priority::Moderate,
+            _ => Priority::VeryLow,
+        }
+    }
+
+    /// True if the message is at least as severe as `threshold`.
+    pub fn at_least(&self, threshold: SyslogSeverity) -> bool {
+        self.severity <= threshold as u8
+    }
+}
+
+/// Parses a facility name (`daemon`, `local0`, ...) or number for `--facility`.
+pub fn parse_facility(name: &str) -> std::result::Result<u8, String> {
+    if let Ok(code) = name.parse::<u8>()
+        && usize::from(code) < FACILITIES.len()
+    {
+        return Ok(code);
+    }
+    FACILITIES
+        .iter()
+        .position(|facility| facility.eq_ignore_ascii_case(name))
+        .map(|code| code as u8)
+        .ok_or_else(|| {
+            format!(
+                "unknown facility (expected one of {})",
+                FACILITIES.join(", ")
+            )
+        })
+}
+
+/// Parses one syslog message. Returns `None` if it doesn't start with a valid `<PRI>`.
+pub fn parse(line: &str) -> Option<SyslogMessage> {
+    let line = line.trim_end_matches(['\r', '\n', '\0']);
+    let rest = line.strip_prefix('<')?;
+    let end = rest.find('>')?;
+    let pri: u8 = rest[..end].parse().ok().filter(|pri| *pri <= 191)?;
+    let rest = &rest[end + 1..];
+
+    let (hostname, program, message) = match rest.strip_prefix("1 ") {
+        Some(rest) => parse_5424(rest)?,
+        None => parse_3164(rest),
+    };
+
+    Some(SyslogMessage {
+        facility: pri / 8,
+        severity: pri % 8,
+        hostname,
+        program,
+        message: message.trim().to_string(),
+    })
+}
+
+type Fields<'a> = (Option<String>, Option<String>, &'a str);
+
+/// `TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA [MSG]`, with `-` for nil values.
+fn parse_5424(rest: &str) -> Option<Fields<'_>> {
+    let mut fields = rest.splitn(6, ' ');
+    let _timestamp = fields.next()?;
+    let hostname = nil_to_none(fields.next()?);
+    let program = nil_to_none(fields.next()?);
+    let _procid = fields.next()?;
+    let _msgid = fields.next()?;
+    let rest = fields.next().unwrap_or("");
+
+    let message = match rest.strip_prefix('-') {
+        Some(message) => message,
+        None => skip_structured_data(rest)?,
+    };
+    Some((
+        hostname,
+        program,
+        message.trim_start().trim_start_matches('\u{feff}'),
+    ))
+}
+
+/// Skips one or more `[id param="value" ...]` elements, honouring `\]` escapes in values.
+fn skip_structured_data(rest: &str) -> Option<&str> {
+    let mut rest = rest;
+    while rest.starts_with('[') {
+        let mut escaped = false;
+        let mut in_value = false;
+        let mut end = None;
+        for (i, c) in rest.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_value = !in_value,
+                ']' if !in_value => {
+                    end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        rest = &rest[end? + 1..];
+    }
+    Some(rest)
+}
+
+/// `Mmm dd hh:mm:ss HOSTNAME TAG[PID]: MSG`. Many devices leave out the timestamp and
+/// hostname, or send free text without a tag, so each part is optional.
+fn parse_3164(rest: &str) -> Fields<'_> {
+    let mut hostname = None;
+    let mut rest = rest;
+
+    if rest.len() > 16
+        && rest.is_char_boundary(15)
+        && is_bsd_timestamp(&rest[..15])
+        && rest.as_bytes()[15] == b' '
+    {
+        rest = &rest[16..];
+        if let Some((host, after)) = rest.split_once(' ')
+            && !host.ends_with(':')
+            && !host.contains('[')
+        {
+            hostname = Some(host.to_string());
+            rest = after;
+        }
+    }
+
+    let tag_end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_./".contains(c)))
+        .unwrap_or(rest.len());
+    let (tag, after) = rest.split_at(tag_end);
+    let after = match after.strip_prefix('[') {
+        Some(pid) => pid.split_once(']').map_or(after, |(_, after)| after),
+        None => after,
+    };
+    match after.strip_prefix(':') {
+        Some(message) if !tag.is_empty() => (hostname, Some(tag.to_string()), message),
+        _ => (hostname, None, rest),
+    }
+}
+
+fn is_bsd_timestamp(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    MONTHS.iter().any(|month| month.as_bytes() == &bytes[..3])
+        && bytes[3] == b' '
+        && (bytes[4] == b' ' || bytes[4].is_ascii_digit())
+        && bytes[5].is_ascii_digit()
+        && bytes[6] == b' '
+        && bytes[7..].iter().enumerate().all(|(i, b)| {
+            if i % 3 == 2 {
+                *b == b':'
+            } else {
+                b.is_ascii_digit()
+            }
+        })
+}
+
+fn nil_to_none(field: &str) -> Option<String> {
+    (field != "-").then(|| field.to_string())
+}
+
+/// Syslog severities, most severe first; the discriminant is the RFC 5424 code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SyslogSeverity {
+    #[cfg_attr(feature = "cli", value(alias = "emergency", alias = "panic"))]
+    Emerg = 0,
+    Alert = 1,
+    #[cfg_attr(feature = "cli", value(alias = "critical"))]
+    Crit = 2,
+    #[cfg_attr(feature = "cli", value(alias = "error"))]
+    Err = 3,
+    #[cfg_attr(feature = "cli", value(alias = "warn"))]
+    Warning = 4,
+    Notice = 5,
+    #[cfg_attr(feature = "cli", value(alias = "informational"))]
+    Info = 6,
+    Debug = 7,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rfc3164() {
+        let message = parse("<34>Oct 11 22:14:15 mymachine su[230]: 'su root' failed\n").unwrap();
+        assert_eq!(message.facility, 4);
+        assert_eq!(message.severity, 2);
+        assert_eq!(message.hostname.as_deref(), Some("mymachine"));
+        assert_eq!(message.program.as_deref(), Some("su"));
+        assert_eq!(message.message, "'su root' failed");
+        assert_eq!(message.priority(), Priority::High);
+
+        let bare = parse("<13>ups: on battery").unwrap();
+        assert_eq!(bare.hostname, None);
+        assert_eq!(bare.program.as_deref(), Some("ups"));
+        assert_eq!(bare.message, "on battery");
+
+        let untagged = parse("<14>Link down on port 3").unwrap();
+        assert_eq!(untagged.program, None);
+        assert_eq!(untagged.message, "Link down on port 3");
+
+        assert_eq!(parse("no priority"), None);
+        assert_eq!(parse("<999>too high"), None);
+    }
+
+    #[test]
+    fn test_parse_rfc5424() {
+        let message = parse(
+            "<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog - ID47 \
+             [exampleSDID@32473 iut=\"3\" eventSource=\"App\\]lication\"] \u{feff}An application event",
+        )
+        .unwrap();
+        assert_eq!(message.facility, 20);
+        assert_eq!(message.severity, 5);
+        assert_eq!(message.hostname.as_deref(), Some("mymachine.example.com"));
+        assert_eq!(message.program.as_deref(), Some("evntslog"));
+        assert_eq!(message.message, "An application event");
+
+        let nil = parse("<11>1 - - - - - - disk failing").unwrap();
+        assert_eq!(nil.hostname, None);
+        assert_eq!(nil.program, None);
+        assert_eq!(nil.message, "disk failing");
+    }
+
+    #[test]
+    fn test_parse_facility() {
+        assert_eq!(parse_facility("daemon"), Ok(3));
+        assert_eq!(parse_facility("LOCAL7"), Ok(23));
+        assert_eq!(parse_facility("16"), Ok(16));
+        assert!(parse_facility("bogus").is_err());
+    }
+}
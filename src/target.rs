@@ -0,0 +1,275 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::api::{Priority, SendRequest};
+use crate::error::{ProwlError, Result};
+
+/// Scheme of a target URL.
+pub const SCHEME: &str = "prowl://";
+
+/// Length of a Prowl API or provider key.
+const KEY_LEN: usize = 40;
+
+/// A Prowl destination as a single URL, for pasting into CI secrets:
+///
+/// ```text
+/// prowl://APIKEY[,APIKEY...]@?priority=high&provider=PROVIDERKEY&app=CI
+/// ```
+///
+/// The `@` and every parameter are optional. Displaying a target masks its keys; use
+/// [`Target::to_url`] for the full URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub api_keys: Vec<String>,
+    pub provider_key: Option<String>,
+    pub application: Option<String>,
+    pub priority: Option<Priority>,
+}
+
+impl Target {
+    /// Whether `value` is meant as a target URL rather than a bare key.
+    pub fn is_url(value: &str) -> bool {
+        value
+            .get(..SCHEME.len())
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+    }
+
+    /// The URL with its keys in full.
+    pub fn to_url(&self) -> String {
+        self.format(|key| key.to_string())
+    }
+
+    fn format(&self, key: impl Fn(&str) -> String) -> String {
+        let keys: Vec<String> = self.api_keys.iter().map(|k| key(k)).collect();
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        if let Some(priority) = self.priority {
+            query.append_pair("priority", priority.name());
+        }
+        if let Some(ref provider_key) = self.provider_key {
+            query.append_pair("provider", &key(provider_key));
+        }
+        if let Some(ref application) = self.application {
+            query.append_pair("app", application);
+        }
+        let query = query.finish();
+
+        if query.is_empty() {
+            format!("{SCHEME}{}", keys.join(","))
+        } else {
+            format!("{SCHEME}{}@?{query}", keys.join(","))
+        }
+    }
+}
+
+impl FromStr for Target {
+    type Err = ProwlError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        // The URL isn't repeated, as it holds secrets.
+        let invalid =
+            |reason: &str| ProwlError::InvalidInput(format!("Invalid target URL: {reason}"));
+        if !Target::is_url(value) {
+            return Err(invalid(
+                "expected prowl://APIKEY@?priority=...&provider=...&app=...",
+            ));
+        }
+
+        let rest = &value[SCHEME.len()..];
+        let (authority, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let keys = match authority.split_once('@') {
+            Some((keys, "")) => keys,
+            Some(_) => return Err(invalid("nothing may follow the \"@\"")),
+            None => authority.trim_end_matches('/'),
+        };
+
+        let api_keys: Vec<String> = keys.split(',').map(|key| key.trim().to_string()).collect();
+        for key in &api_keys {
+            check_key(key).map_err(|reason| invalid(&format!("API key {reason}")))?;
+        }
+
+        let mut target = Target {
+            api_keys,
+            provider_key: None,
+            application: None,
+            priority: None,
+        };
+        for (name, value) in form_urlencoded::parse(query.as_bytes()) {
+            match name.as_ref() {
+                "priority" => {
+                    let priority = value
+                        .parse::<i8>()
+                        .ok()
+                        .and_then(Priority::from_i8)
+                        .or_else(|| Priority::from_name(&value))
+                        .ok_or_else(|| invalid(&format!("unknown priority \"{value}\"")))?;
+                    target.priority = Some(priority);
+                }
+                "provider" | "providerkey" => {
+                    check_key(&value)
+                        .map_err(|reason| invalid(&format!("provider key {reason}")))?;
+                    target.provider_key = Some(value.into_owned());
+                }
+                "app" | "application" => {
+                    if value.is_empty() || value.len() > SendRequest::MAX_APPLICATION_LEN {
+                        return Err(invalid(&format!(
+                            "app must be 1 to {} bytes",
+                            SendRequest::MAX_APPLICATION_LEN
+                        )));
+                    }
+                    target.application = Some(value.into_owned());
+                }
+                other => return Err(invalid(&format!("unknown parameter \"{other}\""))),
+            }
+        }
+        Ok(target)
+    }
+}
+
+/// Shows the target with its keys masked.
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(mask))
+    }
+}
+
+/// Turns keys given as bare keys, comma-separated keys or target URLs into API keys.
+/// Only the keys of a URL are used; see [`recipient_keys`] for recipients.
+pub fn expand_keys<'a>(values: impl IntoIterator<Item = &'a String>) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+    for value in values {
+        if Target::is_url(value) {
+            keys.extend(value.parse::<Target>()?.api_keys);
+        } else {
+            keys.extend(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string),
+            );
+        }
+    }
+    Ok(keys)
+}
+
+/// Like [`expand_keys`], for extra recipients (`--to`, a rule's `to`, mail aliases): their
+/// notification's settings come from elsewhere, so a URL that sets any is rejected rather
+/// than having its settings ignored.
+pub fn recipient_keys<'a>(values: impl IntoIterator<Item = &'a String>) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+    for value in values {
+        if Target::is_url(value) {
+            let target: Target = value.parse()?;
+            if target.priority.is_some()
+                || target.provider_key.is_some()
+                || target.application.is_some()
+            {
+                return Err(ProwlError::InvalidInput(format!(
+                    "Recipient {target} sets priority, provider or app, which only apply to \
+                     the main API key; give the recipient's keys alone"
+                )));
+            }
+            keys.extend(target.api_keys);
+        } else {
+            keys.extend(expand_keys([value])?);
+        }
+    }
+    Ok(keys)
+}
+
+/// `value` with its keys masked if it's a target URL, or masked as a key otherwise.
+pub fn mask_url(value: &str) -> String {
+    match value.parse::<Target>() {
+        Ok(target) => target.to_string(),
+        Err(_) if Target::is_url(value) => format!("{SCHEME}..."),
+        Err(_) => mask(value),
+    }
+}
+
+/// Whether `value` is a well-formed API key: 40 hexadecimal characters.
+pub fn is_api_key(value: &str) -> bool {
+    check_key(value).is_ok()
+}
+
+fn check_key(key: &str) -> std::result::Result<(), &'static str> {
+    if key.len() != KEY_LEN {
+        Err("must be 40 characters")
+    } else if !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+        Err("must be hexadecimal")
+    } else {
+        Ok(())
+    }
+}
+
+/// "0123...4567": the first and last four characters of a key, or all stars if it's short.
+pub fn mask(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        "*".repeat(chars.len())
+    } else {
+        let (start, end) = (&chars[..4], &chars[chars.len() - 4..]);
+        format!(
+            "{}...{}",
+            start.iter().collect::<String>(),
+            end.iter().collect::<String>()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let key = "0123456789abcdef0123456789abcdef01234567";
+        let provider = "f".repeat(40);
+        let url = format!("prowl://{key}@?priority=high&provider={provider}&app=Nightly+CI");
+        let target: Target = url.parse().unwrap();
+        assert_eq!(target.api_keys, [key]);
+        assert_eq!(target.provider_key.as_deref(), Some(provider.as_str()));
+        assert_eq!(target.application.as_deref(), Some("Nightly CI"));
+        assert_eq!(target.priority, Some(Priority::High));
+        assert_eq!(target.to_url(), url);
+        assert_eq!(
+            target.to_string(),
+            "prowl://0123...4567@?priority=high&provider=ffff...ffff&app=Nightly+CI"
+        );
+
+        let bare: Target = format!("prowl://{key},{provider}").parse().unwrap();
+        assert_eq!(bare.api_keys.len(), 2);
+        assert_eq!(bare.priority, None);
+
+        for bad in [
+            "prowl://short@",
+            &format!("prowl://{key}@host"),
+            &format!("prowl://{key}@?priority=urgent"),
+            &format!("prowl://{key}@?colour=red"),
+        ] {
+            let error = bad.parse::<Target>().unwrap_err().to_string();
+            assert!(!error.contains(key), "{error}");
+        }
+    }
+
+    #[test]
+    fn test_recipient_keys() {
+        let key = "0123456789abcdef0123456789abcdef01234567".to_string();
+        let other = "f".repeat(40);
+        let keys = recipient_keys([&format!("prowl://{key}@"), &format!("{other}, {key}")]);
+        assert_eq!(keys.unwrap(), [key.clone(), other, key.clone()]);
+
+        let error = recipient_keys([&format!("prowl://{key}@?priority=high")])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("sets priority, provider or app"), "{error}");
+        assert!(!error.contains(&key), "{error}");
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(mask("0123456789abcdef"), "0123...cdef");
+        assert_eq!(mask("short"), "*****");
+        // Not a valid key, but error messages and history may still show one.
+        assert_eq!(mask("äöüß1234567890ñ"), "äöüß...890ñ");
+    }
+}
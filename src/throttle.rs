@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::api::SendRequest;
+use crate::error::Result;
+use crate::store::LockedStore;
+
+/// Notifications sent with `--throttle`, keyed by dedup key, so repeats inside the window
+/// can be suppressed across separate invocations.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThrottleState {
+    #[serde(default)]
+    pub entries: BTreeMap<String, ThrottleEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThrottleEntry {
+    /// When the notification that opened the window was sent (Unix seconds).
+    pub sent_at: u64,
+    /// When the window closes (Unix seconds).
+    pub until: u64,
+    #[serde(default)]
+    pub suppressed: u32,
+    /// Request to base a "suppressed N repeats" summary on, when `--summary` was given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<SendRequest>,
+}
+
+/// What to do with a throttled notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Send,
+    /// A notification with the same key went out earlier in the window; this is repeat
+    /// number `repeats` since then.
+    Suppress {
+        repeats: u32,
+        until: SystemTime,
+    },
+}
+
+impl ThrottleState {
+    /// Decides whether the notification with `key` goes out at `now`, recording either the
+    /// new window or one more suppressed repeat.
+    pub fn check(
+        &mut self,
+        key: &str,
+        window: Duration,
+        now: SystemTime,
+        summary: Option<SendRequest>,
+    ) -> Decision {
+        let now = unix_seconds(now);
+        if let Some(entry) = self.entries.get_mut(key)
+            && entry.until > now
+        {
+            entry.suppressed += 1;
+            return Decision::Suppress {
+                repeats: entry.suppressed,
+                until: UNIX_EPOCH + Duration::from_secs(entry.until),
+            };
+        }
+
+        self.entries.insert(
+            key.to_string(),
+            ThrottleEntry {
+                sent_at: now,
+                until: now + window.as_secs().max(1),
+                suppressed: 0,
+                summary,
+            },
+        );
+        Decision::Send
+    }
+
+    /// Drops every entry whose window has closed, returning summary notifications for the
+    /// ones that suppressed repeats and asked for a summary.
+    pub fn take_expired(&mut self, now: SystemTime) -> Vec<SendRequest> {
+        let now = unix_seconds(now);
+        let expired: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.until <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|key| {
+                let entry = self.entries.remove(&key)?;
+                let mut request = entry.summary.filter(|_| entry.suppressed > 0)?;
+                let window = Duration::from_secs(entry.until - entry.sent_at);
+                request.description = format!(
+                    "Suppressed {} repeat{} within {} of the first notification",
+                    entry.suppressed,
+                    if entry.suppressed == 1 { "" } else { "s" },
+                    humantime::format_duration(window),
+                );
+                Some(request)
+            })
+            .collect()
+    }
+
+    /// Forgets `key`, so a notification that failed to send doesn't suppress its retries.
+    pub fn forget(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+}
+
+/// The throttle state file, locked while in use.
+pub type ThrottleFile = LockedStore<ThrottleState>;
+
+impl ThrottleState {
+    pub fn open() -> Result<ThrottleFile> {
+        LockedStore::open("throttle.toml", "throttle state")
+    }
+}
+
+/// Dedup key for a notification without `--dedup-key`: a hash of its event and description.
+///
+/// Uses 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust releases, so keys
+/// in an existing state file keep matching after an upgrade.
+pub fn dedup_key(event: &str, description: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in event.bytes().chain([0]).chain(description.bytes()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> SendRequest {
+        SendRequest {
+            event: "disk full".to_string(),
+            description: "/ is 95% full".to_string(),
+            priority: 1,
+            ..SendRequest::example()
+        }
+    }
+
+    #[test]
+    fn test_suppresses_within_window_and_summarizes() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let window = Duration::from_secs(600);
+        let mut state = ThrottleState::default();
+
+        assert_eq!(
+            state.check("disk", window, start, Some(request())),
+            Decision::Send
+        );
+        for repeats in 1..=3 {
+            let now = start + Duration::from_secs(60 * repeats);
+            assert!(matches!(
+                state.check("disk", window, now, None),
+                Decision::Suppress { repeats: r, .. } if r == repeats as u32
+            ));
+        }
+
+        assert!(
+            state
+                .take_expired(start + Duration::from_secs(599))
+                .is_empty()
+        );
+        let summaries = state.take_expired(start + window);
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].event, "disk full");
+        assert_eq!(
+            summaries[0].description,
+            "Suppressed 3 repeats within 10m of the first notification"
+        );
+        assert!(state.entries.is_empty());
+
+        assert_eq!(
+            state.check("disk", window, start + window, None),
+            Decision::Send
+        );
+    }
+
+    #[test]
+    fn test_dedup_key_is_stable() {
+        assert_eq!(dedup_key("a", "b"), dedup_key("a", "b"));
+        assert_ne!(dedup_key("ab", ""), dedup_key("a", "b"));
+        assert_eq!(dedup_key("", ""), "af63bd4c8601b7df");
+    }
+}